@@ -0,0 +1,109 @@
+//  ACCESS.rs
+//    by Lut99
+//
+//  Created:
+//    17 Oct 2026, 00:13:18
+//  Last edited:
+//    17 Oct 2026, 04:37:21
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the access log, which has a line for every request that
+//!   only contains the request headers that operators allowed.
+//
+
+use std::fmt::Write as _;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use hmac::{Hmac, Mac};
+use hyper::{HeaderMap, Method, Version};
+use log::info;
+use sha2::Sha256;
+
+use crate::logging::ACCESS;
+use crate::state::{AccessLog, Context};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Decides how a client shows up in the access log.
+///
+/// # Arguments
+/// - `access`: The [`AccessLog`] that defines whether (and with which key) client IPs are hashed.
+/// - `ip`: The IP of the client, if known.
+///
+/// # Returns
+/// The IP itself, a keyed hash of it (e.g., `#3f9a0c7d12e4b856`), or `-` if it's unknown.
+fn client(access: &AccessLog, ip: Option<IpAddr>) -> String {
+    match ip {
+        Some(ip) if access.hash_client_ip => {
+            // SAFETY: We can call `unwrap()` because HMAC accepts keys of any length.
+            let mut mac: Hmac<Sha256> = Hmac::<Sha256>::new_from_slice(&access.ip_key).unwrap();
+            mac.update(ip.to_string().as_bytes());
+            // NOTE: Eight bytes are plenty to tell clients apart, and keep the lines short
+            mac.finalize().into_bytes()[..8].iter().fold(String::from("#"), |mut hash, byte| {
+                let _ = write!(hash, "{byte:02x}");
+                hash
+            })
+        },
+        Some(ip) => ip.to_string(),
+        None => "-".into(),
+    }
+}
+
+/// Renders the request headers that may be logged.
+///
+/// # Arguments
+/// - `access`: The [`AccessLog`] that defines which headers may be logged.
+/// - `headers`: The headers of the request.
+///
+/// # Returns
+/// A ` name="value"` for every allowed header that the request has (in the order in which they're allowed), or an empty string if it has
+/// none of them.
+fn allowed_headers(access: &AccessLog, headers: &HeaderMap) -> String {
+    let mut res: String = String::new();
+    for name in &access.headers {
+        for value in headers.get_all(name) {
+            let _ = write!(res, " {name}={:?}", String::from_utf8_lossy(value.as_bytes()));
+        }
+    }
+    res
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Logs a line for every request, with its client, method, path, status and duration, and any of the allowed request headers.
+///
+/// Queries are never logged, as they may contain personal data. Neither is the `version_path`, which is usually polled by monitoring.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the configured `access_log`.
+/// - `addr`: The address of the client, if known (i.e., if the router is served with connect info).
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// The response of the rest of the stack, untouched.
+pub async fn log(State(state): State<Arc<Context>>, addr: Option<ConnectInfo<SocketAddr>>, request: Request, next: Next) -> Response {
+    let Some(access) = &state.access_log else { return next.run(request).await };
+    if !state.version_path.is_empty() && request.uri().path() == state.version_path {
+        return next.run(request).await;
+    }
+    let (method, path, version): (Method, String, Version) = (request.method().clone(), request.uri().path().into(), request.version());
+    let client: String = client(access, addr.map(|ConnectInfo(addr)| addr.ip()));
+    let headers: String = allowed_headers(access, request.headers());
+
+    let start: Instant = Instant::now();
+    let res: Response = next.run(request).await;
+    let elapsed: Duration = start.elapsed();
+    info!(target: ACCESS, "{client} \"{method} {path} {version:?}\" {} {}ms{headers}", res.status().as_u16(), elapsed.as_millis());
+    res
+}
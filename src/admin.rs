@@ -0,0 +1,104 @@
+//  ADMIN.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:24:45
+//  Last edited:
+//    17 Oct 2026, 05:45:39
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the read-only admin API, which reports on the running
+//!   server to those who know its token.
+//
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse as _, Response};
+use axum::Json;
+use log::{debug, warn};
+
+use crate::logging::ADMIN;
+use crate::state::Context;
+use crate::www;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Compares two byte strings in time that only depends on their lengths, so the token cannot be guessed byte-by-byte.
+///
+/// # Arguments
+/// - `lhs`: The one byte string.
+/// - `rhs`: The other byte string.
+///
+/// # Returns
+/// True if they are equal, false otherwise.
+fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs).fold(0u8, |acc, (l, r)| acc | (l ^ r)) == 0
+}
+
+/// Checks whether a request carries the admin API's token.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `headers`: The headers of the incoming request.
+///
+/// # Returns
+/// [`None`] if the request may proceed, or a 401 UNAUTHORIZED (or 404 NOT FOUND if the admin API is disabled) to send otherwise.
+fn authorize(state: &Arc<Context>, headers: &HeaderMap) -> Option<Response> {
+    let Some(admin) = &state.admin else { return Some(www::return_error(state, StatusCode::NOT_FOUND).into_response()) };
+    let token: Option<&str> =
+        headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok()).and_then(|value| value.strip_prefix("Bearer "));
+    if token.is_some_and(|token| constant_time_eq(token.trim().as_bytes(), admin.token.as_bytes())) {
+        return None;
+    }
+
+    warn!(target: ADMIN, "[401] Refusing admin API request without a valid token");
+    let (code, mut res_headers, body): (StatusCode, HeaderMap, Body) = www::return_error(state, StatusCode::UNAUTHORIZED);
+    res_headers.insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    Some((code, res_headers, body).into_response())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Lists the connections that are currently open as JSON.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `headers`: The headers of the incoming request, which must authorize it.
+///
+/// # Returns
+/// 200 OK with a list of [`ConnectionSummary`](crate::stats::ConnectionSummary)s as JSON, or 401 UNAUTHORIZED if the request didn't carry
+/// the admin API's token.
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn connections(State(state): State<Arc<Context>>, headers: HeaderMap) -> Response {
+    debug!(target: ADMIN, "Handling GET admin connections");
+    if let Some(res) = authorize(&state, &headers) {
+        return res;
+    }
+    Json(state.stats.connections()).into_response()
+}
+
+/// Summarizes the statistics of the server as JSON.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `headers`: The headers of the incoming request, which must authorize it.
+///
+/// # Returns
+/// 200 OK with a [`StatsSummary`](crate::stats::StatsSummary) as JSON, or 401 UNAUTHORIZED if the request didn't carry the admin API's
+/// token.
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn stats(State(state): State<Arc<Context>>, headers: HeaderMap) -> Response {
+    debug!(target: ADMIN, "Handling GET admin stats");
+    if let Some(res) = authorize(&state, &headers) {
+        return res;
+    }
+    Json(state.stats.summary()).into_response()
+}
@@ -0,0 +1,342 @@
+//  GUARDS.rs
+//    by Lut99
+//
+//  Created:
+//    14 Oct 2026, 14:42:21
+//  Last edited:
+//    17 Oct 2026, 07:43:06
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements middleware that guards the paths against malformed or
+//!   otherwise unwanted requests.
+//
+
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, Bytes};
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::{IntoResponse as _, Response};
+use hyper::body::{Body as HttpBody, Frame, SizeHint};
+use hyper::{header, HeaderMap, Method, StatusCode, Uri, Version};
+use log::{debug, error};
+
+use crate::logging::{GUARDS, RATELIMIT};
+use crate::state::{header_bytes, Context, QuotaTracker};
+use crate::www;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Checks whether the given string is a syntactically valid `Host`-header value (RFC 9110, § 7.2).
+///
+/// # Arguments
+/// - `host`: The value to check.
+///
+/// # Returns
+/// True if it's a valid `uri-host [ ":" port ]`, false otherwise.
+fn is_valid_host(host: &str) -> bool {
+    // Split the port off first
+    let (name, port): (&str, Option<&str>) = if let Some(rest) = host.strip_prefix('[') {
+        // IP-literal
+        let Some((addr, rest)) = rest.split_once(']') else { return false };
+        if addr.is_empty() || !addr.chars().all(|c| c.is_ascii_hexdigit() || matches!(c, ':' | '.')) {
+            return false;
+        }
+        match rest.strip_prefix(':') {
+            Some(port) => (addr, Some(port)),
+            None if rest.is_empty() => (addr, None),
+            None => return false,
+        }
+    } else {
+        let (name, port): (&str, Option<&str>) = match host.rsplit_once(':') {
+            Some((name, port)) => (name, Some(port)),
+            None => (host, None),
+        };
+        // A reg-name consists of unreserved characters, percent-encodings and sub-delimiters
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-._~%!$&'()*+,;=".contains(c)) {
+            return false;
+        }
+        (name, port)
+    };
+    !name.is_empty()
+        && match port {
+            Some(port) => !port.is_empty() && port.len() <= 5 && port.chars().all(|c| c.is_ascii_digit()),
+            None => true,
+        }
+}
+
+
+
+
+
+/***** HELPERS *****/
+/// Wraps a response body to count the bytes that are actually sent towards the client's quota in a [`QuotaTracker`].
+struct Metered {
+    /// The body to count the bytes of.
+    inner:   Body,
+    /// The tracker to count them in.
+    tracker: Arc<QuotaTracker>,
+    /// The IP of the client they are sent to.
+    ip:      IpAddr,
+}
+impl HttpBody for Metered {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let res: Poll<Option<Result<Frame<Bytes>, axum::Error>>> = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &res {
+            if let Some(data) = frame.data_ref() {
+                self.tracker.record(self.ip, data.len() as u64, Instant::now());
+            }
+        }
+        res
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool { self.inner.is_end_stream() }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint { self.inner.size_hint() }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Answers server-wide `OPTIONS *`-requests, which would otherwise not match any path.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// Either the configured `options_status` (204 NO CONTENT by default) with an `Allow`-header listing the methods supported by the server for
+/// `OPTIONS *`; or the response of the `next` service.
+pub async fn server_options(State(state): State<Arc<Context>>, request: Request, next: Next) -> Response {
+    if request.method() == Method::OPTIONS && request.uri() == "*" {
+        debug!(target: GUARDS, "Handling OPTIONS *");
+        let code: StatusCode = StatusCode::from_u16(state.options_status).unwrap_or(StatusCode::NO_CONTENT);
+        let methods: Vec<&str> = www::allowed_methods(&state);
+
+        // Build the response
+        let mut headers: HeaderMap = HeaderMap::new();
+        if let Ok(allow) = HeaderValue::from_str(&methods.join(", ")) {
+            headers.insert(header::ALLOW, allow);
+        }
+        if let Some(server) = &state.server_header {
+            headers.insert(header::SERVER, server.clone());
+        }
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(0));
+        return (code, headers).into_response();
+    }
+    next.run(request).await
+}
+
+
+/// Refuses `TRACE`-requests, which are never echoed back to prevent cross-site tracing.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// Either the configured `trace_status` (405 METHOD NOT ALLOWED by default) for `TRACE`-requests, or the response of the `next` service.
+pub async fn reject_trace(State(state): State<Arc<Context>>, request: Request, next: Next) -> Response {
+    if request.method() == Method::TRACE {
+        let code: StatusCode = StatusCode::from_u16(state.trace_status).unwrap_or(StatusCode::METHOD_NOT_ALLOWED);
+        debug!(target: GUARDS, "[{}] Refusing TRACE-request", code.as_u16());
+        let (code, mut headers, body): (StatusCode, HeaderMap, Body) = www::return_error(&state, code);
+        if code == StatusCode::METHOD_NOT_ALLOWED {
+            if let Ok(allow) = HeaderValue::from_str(&www::allowed_methods(&state).join(", ")) {
+                headers.insert(header::ALLOW, allow);
+            }
+        }
+        return (code, headers, body).into_response();
+    }
+    next.run(request).await
+}
+
+
+/// Rejects requests that treat us like a (forward) proxy.
+///
+/// `CONNECT`-requests are refused, and requests with an absolute-form target (e.g., `GET http://example.com/index.html`) are either
+/// normalized to their path or refused, depending on the configuration.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// Either 405 METHOD NOT ALLOWED for `CONNECT`-requests; 400 BAD REQUEST for absolute-form targets if `reject_absolute_form` is set; or the
+/// response of the `next` service.
+pub async fn reject_proxy_requests(State(state): State<Arc<Context>>, mut request: Request, next: Next) -> Response {
+    if request.method() == Method::CONNECT {
+        debug!(target: GUARDS, "[405] Refusing CONNECT-request");
+        let (code, mut headers, body): (StatusCode, HeaderMap, Body) = www::return_error(&state, StatusCode::METHOD_NOT_ALLOWED);
+        if let Ok(allow) = HeaderValue::from_str(&www::allowed_methods(&state).join(", ")) {
+            headers.insert(header::ALLOW, allow);
+        }
+        return (code, headers, body).into_response();
+    }
+
+    // NOTE: HTTP/2 requests always have absolute URIs (built from their pseudo-headers), so only HTTP/1 ones can be absolute-form
+    if request.version() <= Version::HTTP_11 && request.uri().scheme().is_some() {
+        if state.reject_absolute_form {
+            debug!(target: GUARDS, "[400] Refusing request with absolute-form target '{}'", request.uri());
+            return www::return_error(&state, StatusCode::BAD_REQUEST).into_response();
+        }
+        debug!(target: GUARDS, "Normalizing absolute-form target '{}' to its path", request.uri());
+        let uri: Uri = request.uri().path_and_query().cloned().map(Uri::from).unwrap_or_else(|| Uri::from_static("/"));
+        *request.uri_mut() = uri;
+    }
+    next.run(request).await
+}
+
+
+/// Rejects HTTP/1.1 requests that do not carry exactly one, valid `Host`-header.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// Either 400 BAD REQUEST if the `Host`-header is missing, duplicated or malformed; or the response of the `next` service.
+pub async fn validate_host(State(state): State<Arc<Context>>, request: Request, next: Next) -> Response {
+    if request.version() == Version::HTTP_11 {
+        let mut hosts = request.headers().get_all(header::HOST).iter();
+        match (hosts.next(), hosts.next()) {
+            (Some(host), None) => {
+                if !host.to_str().is_ok_and(is_valid_host) {
+                    debug!(target: GUARDS, "[400] Request has an illegal Host-header {host:?}");
+                    return www::return_error(&state, StatusCode::BAD_REQUEST).into_response();
+                }
+            },
+            (None, _) => {
+                debug!(target: GUARDS, "[400] HTTP/1.1 request is missing a Host-header");
+                return www::return_error(&state, StatusCode::BAD_REQUEST).into_response();
+            },
+            (Some(_), Some(_)) => {
+                debug!(target: GUARDS, "[400] Request has multiple Host-headers");
+                return www::return_error(&state, StatusCode::BAD_REQUEST).into_response();
+            },
+        }
+    }
+    next.run(request).await
+}
+
+
+
+/// Refuses requests from clients that exceeded their `daily_quota_bytes`, and counts the bytes sent to all others.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the configured quota.
+/// - `addr`: The address of the client, if known (i.e., if the router is served with connect info).
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// Either 429 TOO MANY REQUESTS with a `Retry-After`-header set to the number of seconds until the client's window resets; or the response
+/// of the `next` service, with its body counted towards the client's quota.
+pub async fn enforce_daily_quota(
+    State(state): State<Arc<Context>>,
+    addr: Option<ConnectInfo<SocketAddr>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (Some(tracker), Some(ConnectInfo(addr))) = (&state.quota_tracker, addr) else { return next.run(request).await };
+    if let Some(reset) = tracker.check(addr.ip(), Instant::now()) {
+        debug!(target: RATELIMIT, "[429] Client '{}' exceeded its daily quota", addr.ip());
+        let (code, mut headers, body): (StatusCode, HeaderMap, Body) = www::return_error(&state, StatusCode::TOO_MANY_REQUESTS);
+        // NOTE: Round up, such that clients never come back too early
+        headers.insert(header::RETRY_AFTER, HeaderValue::from((reset + Duration::from_millis(999)).as_secs()));
+        return (code, headers, body).into_response();
+    }
+
+    let tracker: Arc<QuotaTracker> = tracker.clone();
+    next.run(request).await.map(|inner| Body::new(Metered { inner, tracker, ip: addr.ip() }))
+}
+
+
+
+/// Refuses new requests with a 503 SERVICE UNAVAILABLE for a while once too many requests are taking too long to handle.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the configured `overload_protection`.
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// Either 503 SERVICE UNAVAILABLE while the server is overloaded, or the response of the `next` service.
+pub async fn shed_overload(State(state): State<Arc<Context>>, request: Request, next: Next) -> Response {
+    let Some(breaker) = &state.overload_breaker else { return next.run(request).await };
+    let Some(in_flight) = breaker.enter(Instant::now()) else {
+        debug!(target: RATELIMIT, "[503] Shedding request for '{}' because the server is overloaded", request.uri().path());
+        return www::return_unavailable(&state).into_response();
+    };
+
+    // NOTE: Only the handling counts, not sending the body
+    let res: Response = next.run(request).await;
+    drop(in_flight);
+    res
+}
+
+
+
+/// Removes the configured `strip_response_headers` from every response.
+///
+/// This runs after every other layer, so it has the last word on what clients see.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the configured `strip_response_headers`.
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// The response of the `next` service, without any of the stripped headers.
+pub async fn strip_response_headers(State(state): State<Arc<Context>>, request: Request, next: Next) -> Response {
+    let mut res: Response = next.run(request).await;
+    for name in &state.stripped_headers {
+        res.headers_mut().remove(name);
+    }
+    res
+}
+
+
+
+/// Replaces responses with unreasonably many or large headers by a 500 INTERNAL SERVER ERROR.
+///
+/// This is a last line of defence against header rules that add up to more than clients (or proxies in between) accept.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the configured `max_response_headers` and `max_response_header_bytes`.
+/// - `request`: The incoming [`Request`].
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// The response of the `next` service, or 500 INTERNAL SERVER ERROR if its headers exceed the limits.
+pub async fn limit_response_headers(State(state): State<Arc<Context>>, request: Request, next: Next) -> Response {
+    let res: Response = next.run(request).await;
+    let bytes: usize = header_bytes(res.headers());
+    if res.headers().len() > state.max_response_headers || bytes > state.max_response_header_bytes {
+        error!(
+            target: GUARDS,
+            "[500] Response has {} header(s) totalling {bytes} byte(s), which exceeds the configured limits",
+            res.headers().len()
+        );
+        return www::return_error(&state, StatusCode::INTERNAL_SERVER_ERROR).into_response();
+    }
+    res
+}
@@ -0,0 +1,78 @@
+//  INFO.rs
+//    by Lut99
+//
+//  Created:
+//    14 Oct 2026, 17:48:06
+//  Last edited:
+//    16 Oct 2026, 12:29:27
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Provides auxiliary paths that report information about the server
+//!   itself.
+//
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::Json;
+use log::debug;
+use serde::Serialize;
+
+use crate::logging::INFO;
+use crate::state::Context;
+
+
+/***** CONSTANTS *****/
+/// The methods that the auxiliary paths may be requested with.
+pub const ALLOWED_METHODS: &str = "GET, HEAD, OPTIONS";
+/// The git commit the server was built from, if given at build time through the `GIT_SHA` environment variable.
+const GIT_SHA: Option<&'static str> = option_env!("GIT_SHA");
+
+
+
+
+
+/***** LIBRARY *****/
+/// The body returned by the [`version()`]-path.
+#[derive(Clone, Debug, Serialize)]
+pub struct VersionInfo {
+    /// The name of the server.
+    pub name:    &'static str,
+    /// The version of the server.
+    pub version: &'static str,
+    /// The git commit the server was built from, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<&'static str>,
+}
+
+
+
+/// Reports the name and version of the server as JSON.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+///
+/// # Returns
+/// 200 OK with a [`VersionInfo`] as JSON.
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn version(State(state): State<Arc<Context>>) -> Json<VersionInfo> {
+    debug!(target: INFO, "Handling GET version");
+    Json(VersionInfo { name: state.name, version: state.version, git_sha: GIT_SHA.filter(|sha| !sha.is_empty()) })
+}
+
+/// Answers `OPTIONS`-requests for any of the auxiliary paths.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+///
+/// # Returns
+/// The configured `options_status` (204 NO CONTENT by default) with an `Allow`-header listing [`ALLOWED_METHODS`].
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn options(State(state): State<Arc<Context>>) -> (StatusCode, [(header::HeaderName, HeaderValue); 1]) {
+    debug!(target: INFO, "Handling OPTIONS for auxiliary path");
+    let code: StatusCode = StatusCode::from_u16(state.options_status).unwrap_or(StatusCode::NO_CONTENT);
+    (code, [(header::ALLOW, HeaderValue::from_static(ALLOWED_METHODS))])
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Jul 2024, 18:59:08
 //  Last edited:
-//    17 Jul 2024, 19:03:51
+//    17 Oct 2026, 00:13:18
 //  Auto updated?
 //    Yes
 //
@@ -14,5 +14,18 @@
 //
 
 // Declare modules
+pub mod access;
+pub mod admin;
+pub mod guards;
+pub mod info;
+pub mod livereload;
+pub mod logging;
+pub mod matcher;
+pub mod proxy;
+pub mod redirects;
+pub mod server;
+pub mod signed;
+pub mod sri;
 pub mod state;
+pub mod stats;
 pub mod www;
@@ -0,0 +1,217 @@
+//  LIVERELOAD.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 18:40:57
+//  Last edited:
+//    16 Oct 2026, 19:20:06
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a Server-Sent Events endpoint that tells browsers when
+//!   the site changes, such that they can reload during development.
+//!   Changes are found by polling the site directory, which is cheap
+//!   enough for the small sites that are developed locally.
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse as _, Response};
+use axum_extra::body::AsyncReadBody;
+use error_trace::trace;
+use hyper::{header, HeaderMap, StatusCode};
+use log::{debug, error, info};
+use tokio::io::{AsyncWriteExt as _, DuplexStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::logging::LIVERELOAD;
+use crate::state::Context;
+use crate::www;
+
+
+/***** CONSTANTS *****/
+/// The event that is sent to browsers when the site changes.
+const RELOAD_EVENT: &[u8] = b"data: reload\n\n";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Recursively collects the files in a directory, together with their modification time and length.
+///
+/// Symlinked directories are not followed, and files that cannot be read are left out.
+///
+/// # Arguments
+/// - `dir`: The directory to collect the files of.
+/// - `files`: The list to push the files to.
+fn collect(dir: &Path, files: &mut Snapshot) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path: PathBuf = entry.path();
+        match entry.file_type() {
+            Ok(ty) if ty.is_dir() => collect(&path, files),
+            Ok(_) => {
+                if let Ok(md) = fs::metadata(&path) {
+                    files.push((path, md.modified().ok(), md.len()));
+                }
+            },
+            Err(_) => continue,
+        }
+    }
+}
+
+/// Takes a snapshot of the files in the site directory.
+///
+/// # Arguments
+/// - `site`: The site directory.
+///
+/// # Returns
+/// The paths of all files in the site, sorted, with their modification time and length. Two snapshots differ if the site changed.
+fn snapshot(site: &Path) -> Snapshot {
+    let mut files: Snapshot = Vec::new();
+    collect(site, &mut files);
+    files.sort_unstable();
+    files
+}
+
+
+
+
+
+/***** HELPERS *****/
+/// The files in the site directory, with their modification time and length.
+type Snapshot = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+
+
+
+
+/***** LIBRARY *****/
+/// Watches the site directory for changes, and tells everyone who subscribed about them.
+#[derive(Debug)]
+pub struct SiteWatcher {
+    /// The channel on which changes are announced.
+    changes: broadcast::Sender<()>,
+    /// Whether the task polling the site directory was started.
+    started: AtomicBool,
+}
+impl Default for SiteWatcher {
+    #[inline]
+    fn default() -> Self { Self { changes: broadcast::channel(1).0, started: AtomicBool::new(false) } }
+}
+impl SiteWatcher {
+    /// Subscribes to the changes of the site directory, starting to watch it if nobody did so before.
+    ///
+    /// # Arguments
+    /// - `site`: The site directory to watch.
+    /// - `interval`: The time between two checks of the site directory.
+    ///
+    /// # Returns
+    /// A receiver that gets a message whenever the site changed.
+    pub fn subscribe(&self, site: &Path, interval: Duration) -> broadcast::Receiver<()> {
+        let changes: broadcast::Receiver<()> = self.changes.subscribe();
+        if !self.started.swap(true, Ordering::SeqCst) {
+            info!(target: LIVERELOAD, "Watching site directory '{}' for changes", site.display());
+            let (site, sender): (PathBuf, broadcast::Sender<()>) = (site.into(), self.changes.clone());
+            tokio::spawn(async move {
+                let mut last: Option<Snapshot> = None;
+                loop {
+                    // Walking the site blocks, so do it on a thread that may
+                    let walker: PathBuf = site.clone();
+                    let current: Snapshot = match tokio::task::spawn_blocking(move || snapshot(&walker)).await {
+                        Ok(current) => current,
+                        Err(err) => {
+                            error!(target: LIVERELOAD, "{}", trace!(("Failed to check site directory for changes"), err));
+                            tokio::time::sleep(interval).await;
+                            continue;
+                        },
+                    };
+                    if last.as_ref().is_some_and(|last| *last != current) {
+                        debug!(target: LIVERELOAD, "Site directory changed; telling {} browser(s) to reload", sender.receiver_count());
+                        // NOTE: It's fine if nobody is listening
+                        let _ = sender.send(());
+                    }
+                    last = Some(current);
+                    tokio::time::sleep(interval).await;
+                }
+            });
+        }
+        changes
+    }
+}
+
+
+
+/// Returns the script that is injected into HTML files to reload them when the site changes.
+///
+/// # Arguments
+/// - `path`: The path on which the events are served.
+/// - `nonce`: The placeholder of the CSP nonce, if any, which is given to the script so the policy allows it.
+///
+/// # Returns
+/// A `<script>`-tag that listens to the events on `path`.
+pub fn script(path: &str, nonce: Option<&str>) -> String {
+    // NOTE: Escape anything that could end the string or the tag
+    let path: String = path
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' | '<' | '>' | '&' => format!("\\u{:04x}", c as u32),
+            c => c.into(),
+        })
+        .collect();
+    let nonce: String = nonce.map(|nonce| format!(" nonce=\"{nonce}\"")).unwrap_or_default();
+    format!("<script{nonce}>new EventSource(\"{path}\").onmessage = () => location.reload();</script>")
+}
+
+/// Streams an event to the browser whenever the site changes, as Server-Sent Events.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+///
+/// # Returns
+/// 200 OK with an endless `text/event-stream`, or 404 NOT FOUND if live-reloading is disabled.
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn events(State(state): State<Arc<Context>>) -> Response {
+    debug!(target: LIVERELOAD, "Handling GET live-reload events");
+    let Some(livereload) = &state.livereload else {
+        return www::return_error(&state, StatusCode::NOT_FOUND).into_response();
+    };
+    let mut changes: broadcast::Receiver<()> =
+        livereload.watcher.subscribe(&state.site, Duration::from_millis(livereload.poll_interval_ms));
+
+    // Write the events into a pipe that is streamed to the browser, until it goes away
+    let (mut writer, reader): (DuplexStream, DuplexStream) = tokio::io::duplex(64);
+    tokio::spawn(async move {
+        // NOTE: Start with a comment, so the response is sent (and the `EventSource` opens) right away
+        if writer.write_all(b": connected\n\n").await.is_err() {
+            return;
+        }
+        loop {
+            match changes.recv().await {
+                Ok(()) | Err(RecvError::Lagged(_)) => {
+                    if writer.write_all(RELOAD_EVENT).await.is_err() {
+                        return;
+                    }
+                },
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+
+    let mut headers: HeaderMap = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    if let Some(server) = &state.server_header {
+        headers.insert(header::SERVER, server.clone());
+    }
+    (StatusCode::OK, headers, Body::new(AsyncReadBody::new(reader))).into_response()
+}
@@ -0,0 +1,205 @@
+//  LOGGING.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 12:29:27
+//  Last edited:
+//    17 Oct 2026, 00:13:18
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the log targets of the subsystems of the server, and a
+//!   logger that filters on them (e.g., as given in `RUST_LOG`).
+//
+
+use std::error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::str::FromStr as _;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+
+/***** CONSTANTS *****/
+/// The log target of the binary itself (startup, signals and shutdown).
+pub const MAIN: &str = "swh";
+/// The log target of the access log.
+pub const ACCESS: &str = "swh::access";
+/// The log target of the admin API.
+pub const ADMIN: &str = "swh::admin";
+/// The log target of the config (loading and validating it).
+pub const CONFIG: &str = "swh::config";
+/// The log target of the guards that check requests and responses before and after handling them.
+pub const GUARDS: &str = "swh::guards";
+/// The log target of the informational endpoints (e.g., the version endpoint and `OPTIONS`).
+pub const INFO: &str = "swh::info";
+/// The log target of the live-reload events.
+pub const LIVERELOAD: &str = "swh::livereload";
+/// The log target of the PROXY protocol.
+pub const PROXY: &str = "swh::proxy";
+/// The log target of the connection and read limits.
+pub const RATELIMIT: &str = "swh::ratelimit";
+/// The log target of the server (accepting and serving connections).
+pub const SERVER: &str = "swh::server";
+/// The log target of the SRI manifest.
+pub const SRI: &str = "swh::sri";
+/// The log target of serving files.
+pub const WWW: &str = "swh::www";
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors thrown when parsing a log filter.
+#[derive(Debug)]
+pub enum Error {
+    /// A directive in the filter has an unknown log level.
+    IllegalLevel { directive: String },
+}
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            IllegalLevel { directive } => write!(f, "Illegal log filter directive '{directive}' (unknown log level)"),
+        }
+    }
+}
+impl error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use Error::*;
+        match self {
+            IllegalLevel { .. } => None,
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Filters log records by their target, in the style of `RUST_LOG` (e.g., `swh::www=debug,swh::sri=warn`).
+#[derive(Clone, Debug)]
+pub struct Filter {
+    /// The level of targets that aren't mentioned by any directive.
+    default:    LevelFilter,
+    /// The level per target prefix, the longest first.
+    directives: Vec<(String, LevelFilter)>,
+}
+impl Filter {
+    /// Parses a Filter from a comma-separated list of directives.
+    ///
+    /// Every directive is either a `<level>`, which sets the level of all targets not mentioned otherwise, or a `<target>=<level>`, which
+    /// sets the level of that target and all targets below it (e.g., `swh=warn` also covers `swh::www`). Empty directives are ignored.
+    ///
+    /// # Arguments
+    /// - `spec`: The directives to parse.
+    ///
+    /// # Returns
+    /// A new Filter that applies the given directives.
+    ///
+    /// # Errors
+    /// This function errors if any of the directives has an unknown level.
+    pub fn parse(spec: &str) -> Result<Self, Error> {
+        let mut filter: Self = Self { default: LevelFilter::Trace, directives: Vec::new() };
+        for directive in spec.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+            let (target, level): (Option<&str>, &str) = match directive.split_once('=') {
+                Some((target, level)) => (Some(target.trim()), level.trim()),
+                None => (None, directive),
+            };
+            let level: LevelFilter = match LevelFilter::from_str(level) {
+                Ok(level) => level,
+                Err(_) => return Err(Error::IllegalLevel { directive: directive.into() }),
+            };
+            match target {
+                Some(target) => filter.directives.push((target.into(), level)),
+                None => filter.default = level,
+            }
+        }
+        // NOTE: The sort is stable, so reversing first makes the last of two directives for the same target win
+        filter.directives.reverse();
+        filter.directives.sort_by(|(lhs, _), (rhs, _)| rhs.len().cmp(&lhs.len()));
+        Ok(filter)
+    }
+
+    /// Returns the level up to which records of the given target are logged.
+    ///
+    /// # Arguments
+    /// - `target`: The target of the records.
+    ///
+    /// # Returns
+    /// The level of the most specific directive that covers `target`, or that of the filter as a whole if none does.
+    pub fn level(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .find(|(prefix, _)| target.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with("::")))
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    /// Returns the most verbose level that any target is logged at.
+    ///
+    /// # Returns
+    /// The maximum of the levels of all directives.
+    pub fn max_level(&self) -> LevelFilter { self.directives.iter().map(|(_, level)| *level).fold(self.default, Ord::max) }
+}
+
+
+
+/// A logger that only passes the records that a [`Filter`] allows on to another logger.
+#[derive(Debug)]
+pub struct FilteredLogger<L> {
+    /// The logger that actually logs the records.
+    inner:  L,
+    /// The filter that decides which records are logged.
+    filter: Filter,
+}
+impl<L> FilteredLogger<L> {
+    /// Constructor for the FilteredLogger.
+    ///
+    /// # Arguments
+    /// - `inner`: The logger that actually logs the records.
+    /// - `filter`: The [`Filter`] that decides which records are logged.
+    ///
+    /// # Returns
+    /// A new FilteredLogger that wraps `inner`.
+    #[inline]
+    pub fn new(inner: L, filter: Filter) -> Self { Self { inner, filter } }
+}
+impl<L: Log> FilteredLogger<L> {
+    /// Installs this logger as the global one.
+    ///
+    /// # Arguments
+    /// - `max`: The most verbose level that the inner logger logs at, which caps that of the filter.
+    ///
+    /// # Errors
+    /// This function errors if a global logger was already installed.
+    pub fn init(self, max: LevelFilter) -> Result<(), log::SetLoggerError>
+    where
+        L: 'static,
+    {
+        let max: LevelFilter = self.filter.max_level().min(max);
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max);
+        Ok(())
+    }
+}
+impl<L: Log> Log for FilteredLogger<L> {
+    #[inline]
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level(metadata.target()) && self.inner.enabled(metadata)
+    }
+
+    #[inline]
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    #[inline]
+    fn flush(&self) { self.inner.flush() }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Jul 2024, 18:54:35
 //  Last edited:
-//    17 Jul 2024, 19:38:02
+//    16 Oct 2026, 12:29:27
 //  Auto updated?
 //    Yes
 //
@@ -12,33 +12,28 @@
 //!   Entrypoint to the `static-website-host` binary.
 //
 
-use std::net::SocketAddr;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
-use axum::extract::Request;
-use axum::routing::get;
-use axum::Router;
 use clap::Parser;
 use error_trace::trace;
 use humanlog::{DebugMode, HumanLogger};
-use hyper::body::Incoming;
-use hyper_util::rt::{TokioExecutor, TokioIo};
-use hyper_util::server::conn::auto::Builder as HyperBuilder;
-use log::{debug, error, info, warn};
+use log::{debug, error, info, warn, LevelFilter};
+use static_website_host::logging::{self, Filter, FilteredLogger, MAIN};
+use static_website_host::{server, signed, www};
 use static_website_host::state::Context;
-use static_website_host::www;
-use tokio::net::{TcpListener, TcpStream};
+use static_website_host::stats::Stats;
 use tokio::runtime::{Builder, Runtime};
 use tokio::signal::unix::{signal, SignalKind};
-use tower_service::Service as _;
 
 
 /***** CONSTANTS *****/
 /// The number of seconds we gracefully shutdown.
 const SHUTDOWN_TIMEOUT_S: u64 = 10 * 60;
+/// The address we bind on if neither the CLI nor the config specifies one.
+const DEFAULT_ADDRESS: SocketAddr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 42080));
 
 
 
@@ -53,8 +48,28 @@ struct Arguments {
     trace: bool,
 
     /// The address on which the server binds itself.
-    #[clap(short, long, default_value = "127.0.0.1:42080")]
-    address:     SocketAddr,
+    #[clap(
+        short,
+        long,
+        help = "The address on which the server binds itself. Overrides the address in the configuration file. If neither is given, binds on \
+                '127.0.0.1:42080'."
+    )]
+    address:     Option<SocketAddr>,
+    /// If given, prints the effective MIME types and exits.
+    #[clap(long, help = "If given, prints the effective mapping of file extensions to MIME types (built-in plus configured ones) and exits.")]
+    list_mime:   bool,
+    /// If given, prints the effective configuration and exits.
+    #[clap(
+        long,
+        help = "If given, prints the fully resolved configuration (after applying defaults and canonicalizing paths) as YAML and exits."
+    )]
+    print_config: bool,
+    /// If given, prints a signed URL for the given path and exits.
+    #[clap(long, help = "If given, prints a signed URL (see `signed_urls` in the configuration file) for the given path and exits.")]
+    sign:        Option<String>,
+    /// The number of seconds for which URLs printed by `--sign` are valid.
+    #[clap(long, default_value = "86400", help = "The number of seconds for which the URL printed by '--sign' is valid.")]
+    expires_in:  u64,
     /// The location to the server configuration.
     #[clap(
         short,
@@ -74,18 +89,29 @@ fn main() {
     // Parse the arguments
     let args = Arguments::parse();
 
-    // Setup the logger
-    if let Err(err) = HumanLogger::terminal(if args.trace { DebugMode::Full } else { DebugMode::Debug }).init() {
-        eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
+    // Setup the logger, filtering on the targets in `RUST_LOG` if given
+    let logger: HumanLogger = HumanLogger::terminal(if args.trace { DebugMode::Full } else { DebugMode::Debug });
+    let filter: Option<Result<Filter, logging::Error>> = std::env::var("RUST_LOG").ok().map(|spec| Filter::parse(&spec));
+    if let Some(Ok(filter)) = filter {
+        if let Err(err) = FilteredLogger::new(logger, filter).init(if args.trace { LevelFilter::Trace } else { LevelFilter::Debug }) {
+            eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
+        }
+    } else {
+        if let Err(err) = logger.init() {
+            eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
+        }
+        if let Some(Err(err)) = filter {
+            warn!(target: MAIN, "{}", trace!(("Ignoring 'RUST_LOG'"), err));
+        }
     }
-    info!("{} v{}", env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"));
+    info!(target: MAIN, "{} v{}", env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"));
 
     // Create the tokio runtime
-    debug!("Creating tokio runtime...");
+    debug!(target: MAIN, "Creating tokio runtime...");
     let runtime: Runtime = match Builder::new_multi_thread().enable_all().build() {
         Ok(runtime) => runtime,
         Err(err) => {
-            error!("{}", trace!(("Failed to create tokio runtime"), err));
+            error!(target: MAIN, "{}", trace!(("Failed to create tokio runtime"), err));
             std::process::exit(1);
         },
     };
@@ -94,96 +120,122 @@ fn main() {
     let state: Arc<Context> = match Context::new(env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"), &args.config_path) {
         Ok(state) => Arc::new(state),
         Err(err) => {
-            error!("{}", trace!(("Failed to initialize server context"), err));
+            error!(target: MAIN, "{}", trace!(("Failed to initialize server context"), err));
             std::process::exit(1);
         },
     };
 
-    // Build the paths
-    let www: Router = Router::new().route("/", get(www::handle)).route("/*path", get(www::handle)).with_state(state.clone());
-    let router: IntoMakeServiceWithConnectInfo<Router, SocketAddr> = Router::new().nest("/", www).into_make_service_with_connect_info();
+    // Print the MIME types instead of running the server if told to do so
+    if args.list_mime {
+        for (ext, mime) in www::mime_types(&state) {
+            println!(".{ext:<12} {mime}");
+        }
+        println!("{:<13} {}", "(other)", www::DEFAULT_MIME_TYPE);
+        std::process::exit(0);
+    }
 
-    // Run the main async function
-    runtime.block_on(async move {
-        // Bind the TCP Listener
-        debug!("Binding server on '{}'...", args.address);
-        let listener: TcpListener = match TcpListener::bind(args.address).await {
-            Ok(listener) => listener,
+    // Print the config instead of running the server if told to do so
+    if args.print_config {
+        match serde_yml::to_string(&*state) {
+            Ok(config) => {
+                print!("{config}");
+                std::process::exit(0);
+            },
             Err(err) => {
-                error!("{}", trace!(("Failed to bind server to '{}'", args.address), err));
+                error!(target: MAIN, "{}", trace!(("Failed to serialize effective configuration"), err));
                 std::process::exit(1);
             },
+        }
+    }
+
+    // Print a signed URL instead of running the server if told to do so
+    if let Some(path) = &args.sign {
+        let Some(signed) = &state.signed_urls else {
+            error!(target: MAIN, "Cannot sign URLs without `signed_urls` in the configuration file");
+            std::process::exit(1);
         };
+        let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        println!("{path}?{}", signed::sign(&signed.secret, path, now + args.expires_in));
+        std::process::exit(0);
+    }
 
-        // Accept new connections!
-        info!("Initialization OK, awaiting connections...");
+    // Resolve the address to bind on
+    let address: SocketAddr = args.address.or(state.address).unwrap_or(DEFAULT_ADDRESS);
+
+    // Run the main async function
+    let stats: Arc<Stats> = state.stats.clone();
+    let reloadable: Arc<Context> = state.clone();
+    runtime.block_on(async move {
         tokio::select! {
-            _ = async move {
-                loop {
-                    // Accept a new connection
-                    let (socket, remote_addr): (TcpStream, SocketAddr) = match listener.accept().await {
-                        Ok(res) => res,
-                        Err(err) => {
-                            error!("{}", trace!(("Failed to accept incoming connection"), err));
-                            std::process::exit(1);
-                        },
-                    };
-
-                    // Move the rest to a separate task
-                    let router: IntoMakeServiceWithConnectInfo<_, _> = router.clone();
-                    tokio::spawn(async move {
-                        debug!("Handling incoming connection from '{remote_addr}'");
-
-                        // Build  the service
-                        let service = hyper::service::service_fn(|request: Request<Incoming>| {
-                            // Sadly, we must `move` again because this service could be called multiple times (at least according to the typesystem)
-                            let mut router = router.clone();
-                            async move {
-                                // SAFETY: We can call `unwrap()` because the call returns an infallible.
-                                router.call(remote_addr).await.unwrap().call(request).await
-                            }
-                        });
-
-                        // Create a service that handles this for us
-                        let socket: TokioIo<_> = TokioIo::new(socket);
-                        if let Err(err) = HyperBuilder::new(TokioExecutor::new()).serve_connection_with_upgrades(socket, service).await {
-                            error!("{}", trace!(("Failed to serve incoming connection"), *err));
-                        }
-                    });
-                }
-            } => {
-                unreachable!();
+            res = server::serve(address, state) => match res {
+                Ok(never) => match never {},
+                Err(err) => {
+                    error!(target: MAIN, "{}", trace!(("Failed to run server"), err));
+                    std::process::exit(1);
+                },
             },
 
             _ = async move {
                 match signal(SignalKind::interrupt()) {
                     Ok(mut sign) => sign.recv().await,
                     Err(err) => {
-                        warn!("{}", trace!(("Failed to register SIGINT signal handler"), err));
-                        warn!("Graceful shutdown by Ctrl+C disabled");
+                        warn!(target: MAIN, "{}", trace!(("Failed to register SIGINT signal handler"), err));
+                        warn!(target: MAIN, "Graceful shutdown by Ctrl+C disabled");
                         None
                     },
                 }
             } => {
-                debug!("Received SIGINT");
+                debug!(target: MAIN, "Received SIGINT");
             },
             _ = async move {
                 match signal(SignalKind::terminate()) {
                     Ok(mut sign) => sign.recv().await,
                     Err(err) => {
-                        warn!("{}", trace!(("Failed to register SIGTERM signal handler"), err));
-                        warn!("Graceful shutdown by Docker disabled");
+                        warn!(target: MAIN, "{}", trace!(("Failed to register SIGTERM signal handler"), err));
+                        warn!(target: MAIN, "Graceful shutdown by Docker disabled");
                         None
                     },
                 }
             } => {
-                debug!("Received SIGTERM");
+                debug!(target: MAIN, "Received SIGTERM");
             },
+            _ = async move {
+                match signal(SignalKind::user_defined1()) {
+                    // Keep dumping for as long as the server runs
+                    Ok(mut sign) => while sign.recv().await.is_some() {
+                        info!(target: MAIN, "Stats: {stats}");
+                    },
+                    Err(err) => {
+                        warn!(target: MAIN, "{}", trace!(("Failed to register SIGUSR1 signal handler"), err));
+                        warn!(target: MAIN, "Dumping stats by SIGUSR1 disabled");
+                    },
+                }
+                // Never complete, since that would stop the server
+                std::future::pending::<()>().await
+            } => {},
+            _ = async move {
+                match signal(SignalKind::hangup()) {
+                    // Keep reloading for as long as the server runs
+                    Ok(mut sign) => while sign.recv().await.is_some() {
+                        match reloadable.reload_redirects() {
+                            Ok(Some(len)) => info!(target: MAIN, "Reloaded {len} redirect(s)"),
+                            Ok(None) => info!(target: MAIN, "No redirect file to reload"),
+                            Err(err) => error!(target: MAIN, "{}", trace!(("Failed to reload redirects (keeping the current ones)"), err)),
+                        }
+                    },
+                    Err(err) => {
+                        warn!(target: MAIN, "{}", trace!(("Failed to register SIGHUP signal handler"), err));
+                        warn!(target: MAIN, "Reloading redirects by SIGHUP disabled");
+                    },
+                }
+                // Never complete, since that would stop the server
+                std::future::pending::<()>().await
+            } => {},
         }
     });
 
     // When the server stops, quit the runtime too
-    info!("Terminating tokio runtime ({SHUTDOWN_TIMEOUT_S}s timeout)...");
+    info!(target: MAIN, "Terminating tokio runtime ({SHUTDOWN_TIMEOUT_S}s timeout)...");
     runtime.shutdown_timeout(Duration::from_secs(SHUTDOWN_TIMEOUT_S));
-    info!("Done.");
+    info!(target: MAIN, "Done.");
 }
@@ -0,0 +1,224 @@
+//  MATCHER.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 05:38:48
+//  Last edited:
+//    16 Oct 2026, 05:38:48
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a matcher for path rules (e.g., the `not_found_overrides`
+//!   and `index_overrides`), which is compiled once when loading the
+//!   config and then shared by every feature that matches paths.
+//
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Matches a single path component against a wildcard pattern, where `*` matches any number of characters and `?` any single one.
+///
+/// # Arguments
+/// - `pattern`: The pattern to match.
+/// - `text`: The path component to match it against.
+///
+/// # Returns
+/// True if the whole of `text` matches `pattern`, false otherwise.
+fn wildcard_matches(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t): (usize, usize) = (0, 0);
+    // The position of the last `*` in the pattern, and the position in the text it was tried at
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` eat one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Splits a path into its normal components.
+///
+/// # Arguments
+/// - `path`: The path to split.
+///
+/// # Returns
+/// The components of `path` that are names (i.e., skipping roots and `.`), or [`None`] if any of them isn't valid UTF-8.
+fn components(path: &Path) -> Option<Vec<&str>> {
+    path.components()
+        .filter_map(|comp| match comp {
+            Component::Normal(name) => Some(name.to_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+
+
+
+
+/***** HELPERS *****/
+/// A single component of a compiled pattern.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    /// Matches exactly this component.
+    Literal(String),
+    /// Matches any component that matches this wildcard pattern (with `*` and `?`).
+    Wildcard(String),
+    /// Matches any number of components (including none), written as `**`.
+    Any,
+}
+
+/// A pattern that contains wildcards, compiled into its segments.
+#[derive(Clone, Debug)]
+struct Glob<T> {
+    /// The segments of the pattern.
+    segments:    Vec<Segment>,
+    /// How specific the pattern is; more specific patterns take precedence.
+    specificity: (usize, usize),
+    /// The value of the pattern.
+    value:       T,
+}
+impl<T> Glob<T> {
+    /// Checks whether a path starts with a match of this pattern.
+    ///
+    /// # Arguments
+    /// - `path`: The components of the path to match.
+    ///
+    /// # Returns
+    /// True if the components of this pattern match the first components of `path`, false otherwise.
+    fn matches(&self, path: &[&str]) -> bool {
+        /// Recursively matches the given segments against the start of the given path.
+        fn matches_start(segments: &[Segment], path: &[&str]) -> bool {
+            match segments.split_first() {
+                None => true,
+                Some((Segment::Any, rest)) => (0..=path.len()).any(|skip| matches_start(rest, &path[skip..])),
+                Some((Segment::Literal(name), rest)) => path.first().is_some_and(|comp| comp == name) && matches_start(rest, &path[1..]),
+                Some((Segment::Wildcard(pattern), rest)) => {
+                    path.first().is_some_and(|comp| wildcard_matches(pattern, comp)) && matches_start(rest, &path[1..])
+                },
+            }
+        }
+        matches_start(&self.segments, path)
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Matches paths against a set of path rules, each with a value.
+///
+/// Patterns are relative to the site root (leading and trailing slashes are ignored) and match every path that starts with them. Their
+/// components may contain `*` (any number of characters) and `?` (a single character), or be `**` (any number of components).
+///
+/// If multiple patterns match, the most specific one wins: the one with the most components (besides `**`), then the one with the most
+/// components without wildcards. Remaining ties are won by patterns without any wildcards, and then by the pattern that sorts first.
+#[derive(Clone, Debug)]
+pub struct PathMatcher<T> {
+    /// The patterns without any wildcards, by their components.
+    literals:  HashMap<Vec<String>, T>,
+    /// The patterns with wildcards, the most specific first.
+    globs:     Vec<Glob<T>>,
+    /// The maximum number of components of any of the patterns without wildcards.
+    max_depth: usize,
+}
+impl<T> Default for PathMatcher<T> {
+    #[inline]
+    fn default() -> Self { Self { literals: HashMap::new(), globs: Vec::new(), max_depth: 0 } }
+}
+impl<T> PathMatcher<T> {
+    /// Constructor for the PathMatcher that compiles the given patterns.
+    ///
+    /// # Arguments
+    /// - `rules`: The patterns to match, each with the value that is returned for paths matching it.
+    ///
+    /// # Returns
+    /// A new PathMatcher for the given `rules`.
+    pub fn new<'p>(rules: impl IntoIterator<Item = (&'p str, T)>) -> Self {
+        let mut rules: Vec<(&str, T)> = rules.into_iter().collect();
+        rules.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+
+        let mut matcher: Self = Self::default();
+        for (pattern, value) in rules {
+            let segments: Vec<Segment> = pattern
+                .split('/')
+                .filter(|comp| !comp.is_empty() && *comp != ".")
+                .map(|comp| match comp {
+                    "**" => Segment::Any,
+                    comp if comp.contains(['*', '?']) => Segment::Wildcard(comp.into()),
+                    comp => Segment::Literal(comp.into()),
+                })
+                .collect();
+            if segments.iter().all(|segment| matches!(segment, Segment::Literal(_))) {
+                let names: Vec<String> = segments
+                    .into_iter()
+                    .filter_map(|segment| if let Segment::Literal(name) = segment { Some(name) } else { None })
+                    .collect();
+                matcher.max_depth = matcher.max_depth.max(names.len());
+                // NOTE: The patterns are sorted, so the first of two equivalent ones wins
+                matcher.literals.entry(names).or_insert(value);
+            } else {
+                let specificity: (usize, usize) = (
+                    segments.iter().filter(|segment| **segment != Segment::Any).count(),
+                    segments.iter().filter(|segment| matches!(segment, Segment::Literal(_))).count(),
+                );
+                matcher.globs.push(Glob { segments, specificity, value });
+            }
+        }
+        // NOTE: The sort is stable, so ties keep the pattern order
+        matcher.globs.sort_by_key(|glob| Reverse(glob.specificity));
+        matcher
+    }
+
+    /// Finds the value of the most specific pattern that matches a path.
+    ///
+    /// # Arguments
+    /// - `path`: The (normalized) request path, relative to the site root.
+    ///
+    /// # Returns
+    /// The value of the most specific pattern matching `path`, or [`None`] if none does.
+    pub fn find(&self, path: &Path) -> Option<&T> {
+        let comps: Vec<&str> = components(path)?;
+
+        // Find the longest pattern without wildcards that matches
+        let literal: Option<(usize, &T)> = (0..=comps.len().min(self.max_depth)).rev().find_map(|depth| {
+            let key: Vec<String> = comps[..depth].iter().map(|comp| (*comp).into()).collect();
+            self.literals.get(&key).map(|value| (depth, value))
+        });
+
+        // Only patterns with wildcards that are more specific can beat that
+        for glob in &self.globs {
+            if literal.is_some_and(|(depth, _)| glob.specificity <= (depth, depth)) {
+                break;
+            }
+            if glob.matches(&comps) {
+                return Some(&glob.value);
+            }
+        }
+        literal.map(|(_, value)| value)
+    }
+
+    /// Returns whether this matcher has any patterns.
+    ///
+    /// # Returns
+    /// True if it has none, false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.literals.is_empty() && self.globs.is_empty() }
+}
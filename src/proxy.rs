@@ -0,0 +1,261 @@
+//  PROXY.rs
+//    by Lut99
+//
+//  Created:
+//    14 Oct 2026, 11:36:36
+//  Last edited:
+//    14 Oct 2026, 11:36:36
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements parsing of the [PROXY protocol](https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt)
+//!   (both v1 and v2) that load balancers use to tell us the real client
+//!   address.
+//
+
+use std::error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt as _;
+use tokio::net::TcpStream;
+
+
+/***** CONSTANTS *****/
+/// The signature with which every v1 header starts.
+const V1_SIGNATURE: &[u8] = b"PROXY ";
+/// The maximum length of a v1 header, including the CRLF.
+const V1_MAX_LEN: usize = 107;
+/// The signature with which every v2 header starts.
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+/// The length of the fixed part of a v2 header.
+const V2_HEADER_LEN: usize = 16;
+
+/// The number of times we attempt to peek before giving up on waiting for more bytes.
+const PEEK_ATTEMPTS: usize = 50;
+/// The time between two peek attempts.
+const PEEK_INTERVAL: Duration = Duration::from_millis(10);
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors thrown when reading PROXY protocol headers.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to read from the socket.
+    Read { err: std::io::Error },
+    /// The connection was closed before a complete header was received.
+    UnexpectedEof,
+    /// A v1 header was not terminated within the maximum header length.
+    V1TooLong,
+    /// A v1 header was not valid.
+    V1Illegal { raw: String },
+    /// A v2 header was not valid.
+    V2Illegal { what: &'static str },
+}
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            Read { .. } => write!(f, "Failed to read from socket"),
+            UnexpectedEof => write!(f, "Connection closed before a complete PROXY protocol header was received"),
+            V1TooLong => write!(f, "PROXY protocol v1 header is not terminated within {V1_MAX_LEN} bytes"),
+            V1Illegal { raw } => write!(f, "Illegal PROXY protocol v1 header {raw:?}"),
+            V2Illegal { what } => write!(f, "Illegal PROXY protocol v2 header: {what}"),
+        }
+    }
+}
+impl error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use Error::*;
+        match self {
+            Read { err } => Some(err),
+            UnexpectedEof => None,
+            V1TooLong => None,
+            V1Illegal { .. } => None,
+            V2Illegal { .. } => None,
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Peeks bytes from the given stream until the given predicate is satisfied, the buffer is full or we ran out of patience.
+///
+/// # Arguments
+/// - `stream`: The [`TcpStream`] to peek from.
+/// - `buf`: The buffer to peek into.
+/// - `done`: A predicate that decides whether the bytes peeked so far are enough.
+///
+/// # Returns
+/// The number of bytes peeked into `buf`. This is 0 if the connection has been closed.
+///
+/// # Errors
+/// This function errors if we failed to peek from the socket.
+async fn peek_until(stream: &TcpStream, buf: &mut [u8], done: impl Fn(&[u8]) -> bool) -> Result<usize, Error> {
+    let mut len: usize = 0;
+    for _ in 0..PEEK_ATTEMPTS {
+        len = stream.peek(buf).await.map_err(|err| Error::Read { err })?;
+        if len == 0 || len == buf.len() || done(&buf[..len]) {
+            return Ok(len);
+        }
+        // Give the client some time to send the rest
+        tokio::time::sleep(PEEK_INTERVAL).await;
+    }
+    Ok(len)
+}
+
+/// Parses a v1 (text) header.
+///
+/// # Arguments
+/// - `raw`: The header, excluding the CRLF.
+///
+/// # Returns
+/// The [`Header`] that was parsed.
+///
+/// # Errors
+/// This function errors if the header is not valid.
+fn parse_v1(raw: &[u8]) -> Result<Header, Error> {
+    let illegal = || Error::V1Illegal { raw: String::from_utf8_lossy(raw).into() };
+    let text: &str = std::str::from_utf8(raw).map_err(|_| illegal())?;
+
+    // Split it into its fields
+    let mut fields = text.split(' ').skip(1);
+    match fields.next() {
+        Some("TCP4" | "TCP6") => {},
+        // The receiver must ignore anything after `UNKNOWN`
+        Some("UNKNOWN") => return Ok(Header::Local),
+        _ => return Err(illegal()),
+    }
+    let src: IpAddr = fields.next().and_then(|ip| ip.parse().ok()).ok_or_else(illegal)?;
+    let _dst: IpAddr = fields.next().and_then(|ip| ip.parse().ok()).ok_or_else(illegal)?;
+    let sport: u16 = fields.next().and_then(|port| port.parse().ok()).ok_or_else(illegal)?;
+    let _dport: u16 = fields.next().and_then(|port| port.parse().ok()).ok_or_else(illegal)?;
+    if fields.next().is_some() {
+        return Err(illegal());
+    }
+    Ok(Header::Proxied(SocketAddr::new(src, sport)))
+}
+
+/// Parses a v2 (binary) header.
+///
+/// # Arguments
+/// - `header`: The fixed, 16-byte part of the header.
+/// - `body`: The variable part of the header that follows it.
+///
+/// # Returns
+/// The [`Header`] that was parsed.
+///
+/// # Errors
+/// This function errors if the header is not valid.
+fn parse_v2(header: &[u8; V2_HEADER_LEN], body: &[u8]) -> Result<Header, Error> {
+    if header[12] >> 4 != 0x2 {
+        return Err(Error::V2Illegal { what: "unsupported version" });
+    }
+    match header[12] & 0x0F {
+        // LOCAL; the connection was not proxied (e.g., a health check by the proxy itself)
+        0x0 => return Ok(Header::Local),
+        // PROXY
+        0x1 => {},
+        _ => return Err(Error::V2Illegal { what: "unsupported command" }),
+    }
+
+    // Parse the addresses based on the family
+    match header[13] >> 4 {
+        // AF_INET
+        0x1 => {
+            if body.len() < 12 {
+                return Err(Error::V2Illegal { what: "IPv4 address block too short" });
+            }
+            let ip: Ipv4Addr = Ipv4Addr::new(body[0], body[1], body[2], body[3]);
+            let port: u16 = u16::from_be_bytes([body[8], body[9]]);
+            Ok(Header::Proxied(SocketAddr::new(ip.into(), port)))
+        },
+        // AF_INET6
+        0x2 => {
+            if body.len() < 36 {
+                return Err(Error::V2Illegal { what: "IPv6 address block too short" });
+            }
+            let mut octets: [u8; 16] = [0; 16];
+            octets.copy_from_slice(&body[..16]);
+            let port: u16 = u16::from_be_bytes([body[32], body[33]]);
+            Ok(Header::Proxied(SocketAddr::new(Ipv6Addr::from(octets).into(), port)))
+        },
+        // AF_UNSPEC, AF_UNIX or something unknown; we can't use these addresses
+        _ => Ok(Header::Local),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Describes the outcome of reading a PROXY protocol header.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Header {
+    /// The connection did not start with a PROXY protocol header.
+    Missing,
+    /// There was a header, but it did not carry a (usable) client address. The peer address should be used instead.
+    Local,
+    /// There was a header that carried the given client address.
+    Proxied(SocketAddr),
+}
+
+
+
+/// Reads a PROXY protocol header (v1 or v2) from the start of the given stream.
+///
+/// If the stream does not start with a header, nothing is consumed. Otherwise, exactly the header is consumed, so the remainder of the stream
+/// can be handed to hyper as-is.
+///
+/// # Arguments
+/// - `stream`: The freshly accepted [`TcpStream`] to read the header from.
+///
+/// # Returns
+/// A [`Header`] describing what was found.
+///
+/// # Errors
+/// This function errors if we failed to read from the stream, or if it started with a PROXY protocol signature but the header was not valid.
+pub async fn read_header(stream: &mut TcpStream) -> Result<Header, Error> {
+    // Peek enough to see which signature (if any) we're dealing with
+    let mut buf: [u8; V1_MAX_LEN] = [0; V1_MAX_LEN];
+    let len: usize = peek_until(stream, &mut buf[..V2_HEADER_LEN], |data| {
+        let could_be = |sig: &[u8]| sig.starts_with(&data[..data.len().min(sig.len())]);
+        // We know enough once we have a full signature, or once it can't become one anymore
+        data.starts_with(V1_SIGNATURE) || data.starts_with(V2_SIGNATURE) || (!could_be(V1_SIGNATURE) && !could_be(V2_SIGNATURE))
+    })
+    .await?;
+
+    if buf[..len].starts_with(V1_SIGNATURE) {
+        // Find the end of the line
+        let len: usize = peek_until(stream, &mut buf, |data| data.windows(2).any(|w| w == b"\r\n")).await?;
+        let end: usize = match buf[..len].windows(2).position(|w| w == b"\r\n") {
+            Some(end) => end,
+            None if len < V1_MAX_LEN => return Err(Error::UnexpectedEof),
+            None => return Err(Error::V1TooLong),
+        };
+
+        // Consume it and parse
+        stream.read_exact(&mut buf[..end + 2]).await.map_err(|err| Error::Read { err })?;
+        parse_v1(&buf[..end])
+    } else if buf[..len].starts_with(V2_SIGNATURE) {
+        // Read the fixed part of the header, which tells us how much follows
+        let mut header: [u8; V2_HEADER_LEN] = [0; V2_HEADER_LEN];
+        stream.read_exact(&mut header).await.map_err(|err| Error::Read { err })?;
+        let mut body: Vec<u8> = vec![0; u16::from_be_bytes([header[14], header[15]]) as usize];
+        stream.read_exact(&mut body).await.map_err(|err| Error::Read { err })?;
+        parse_v2(&header, &body)
+    } else {
+        Ok(Header::Missing)
+    }
+}
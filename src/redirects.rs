@@ -0,0 +1,226 @@
+//  REDIRECTS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 04:59:39
+//  Last edited:
+//    16 Oct 2026, 04:59:39
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a map of redirects (e.g., from the URLs of an old site to
+//!   the new one) that is loaded from a separate YAML or CSV file.
+//
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use hyper::header::HeaderValue;
+use hyper::StatusCode;
+use serde::Deserialize;
+
+
+/***** ERRORS *****/
+/// Defines errors thrown when loading a redirect file.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to read the redirect file.
+    Read { path: PathBuf, err: std::io::Error },
+    /// Failed to parse the redirect file as YAML.
+    ParseYaml { path: PathBuf, err: serde_yml::Error },
+    /// A line in a CSV redirect file did not have two or three fields.
+    ParseCsv { path: PathBuf, line: usize },
+    /// A redirect's source is not an absolute path.
+    IllegalFrom { from: String },
+    /// A redirect's target is not a valid `Location`.
+    IllegalTo { from: String, to: String },
+    /// A redirect's status code is not a redirection.
+    IllegalStatus { from: String, status: String },
+}
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            Read { path, .. } => write!(f, "Failed to read redirect file '{}'", path.display()),
+            ParseYaml { path, .. } => write!(f, "Failed to parse redirect file '{}' as YAML", path.display()),
+            ParseCsv { path, line } => write!(f, "Line {line} in redirect file '{}' is not 'from,to' or 'from,to,status'", path.display()),
+            IllegalFrom { from } => write!(f, "Illegal redirect source '{from}' (must start with '/')"),
+            IllegalTo { from, to } => write!(f, "Illegal redirect target '{to}' for '{from}'"),
+            IllegalStatus { from, status } => write!(f, "Illegal redirect status {status} for '{from}' (must be 301, 302, 307 or 308)"),
+        }
+    }
+}
+impl error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use Error::*;
+        match self {
+            Read { err, .. } => Some(err),
+            ParseYaml { err, .. } => Some(err),
+            ParseCsv { .. } => None,
+            IllegalFrom { .. } => None,
+            IllegalTo { .. } => None,
+            IllegalStatus { .. } => None,
+        }
+    }
+}
+
+
+
+
+
+/***** HELPERS *****/
+/// A single redirect as given in a YAML redirect file.
+#[derive(Clone, Debug, Deserialize)]
+struct Entry {
+    /// The path that is redirected. Redirects all paths with that prefix if it ends in `*`.
+    from:   String,
+    /// The target that is redirected to. For prefix redirects, a trailing `*` is replaced by the rest of the path.
+    to:     String,
+    /// The status code of the redirect.
+    #[serde(default = "default_status")]
+    status: u16,
+}
+
+/// Returns the default status code of redirects.
+#[inline]
+fn default_status() -> u16 { 301 }
+
+
+
+
+
+/***** LIBRARY *****/
+/// The target of a single redirect.
+#[derive(Clone, Debug)]
+pub struct Redirect {
+    /// The target that is redirected to.
+    pub to:     String,
+    /// The status code with which it is redirected.
+    pub status: StatusCode,
+}
+
+
+
+/// Maps paths to the targets they are redirected to.
+#[derive(Clone, Debug, Default)]
+pub struct Redirects {
+    /// The redirects for exact paths.
+    exact:    HashMap<String, Redirect>,
+    /// The redirects for path prefixes (without the `*`), the longest first.
+    prefixes: Vec<(String, Redirect)>,
+}
+impl Redirects {
+    /// Loads the redirects from a file.
+    ///
+    /// Files with a `.csv`-extension are read as lines of `from,to[,status]` (skipping empty lines, lines starting with `#` and a
+    /// `from,to,status` header). Anything else is read as a YAML list of `from`, `to` and (optional) `status`.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to load.
+    ///
+    /// # Returns
+    /// A new Redirects with the redirects in the file.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read or parse the file, or if any of the redirects in it is illegal.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let raw: String = match fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(err) => return Err(Error::Read { path: path.into(), err }),
+        };
+
+        // Parse the entries
+        let entries: Vec<(String, String, String)> = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+            let mut entries: Vec<(String, String, String)> = Vec::new();
+            for (i, line) in raw.lines().enumerate() {
+                let line: &str = line.trim();
+                if line.is_empty() || line.starts_with('#') || (i == 0 && line.starts_with("from,")) {
+                    continue;
+                }
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                match fields.as_slice() {
+                    [from, to] => entries.push(((*from).into(), (*to).into(), default_status().to_string())),
+                    [from, to, status] => entries.push(((*from).into(), (*to).into(), (*status).into())),
+                    _ => return Err(Error::ParseCsv { path: path.into(), line: i + 1 }),
+                }
+            }
+            entries
+        } else {
+            match serde_yml::from_str::<Vec<Entry>>(&raw) {
+                Ok(entries) => entries.into_iter().map(|entry| (entry.from, entry.to, entry.status.to_string())).collect(),
+                Err(err) => return Err(Error::ParseYaml { path: path.into(), err }),
+            }
+        };
+
+        // Validate and sort them
+        let mut redirects: Self = Self::default();
+        for (from, to, status) in entries {
+            if !from.starts_with('/') {
+                return Err(Error::IllegalFrom { from });
+            }
+            if to.is_empty() || HeaderValue::from_str(&to).is_err() {
+                return Err(Error::IllegalTo { from, to });
+            }
+            let status: StatusCode = match status.parse::<u16>().ok().and_then(|code| StatusCode::from_u16(code).ok()) {
+                Some(code) if matches!(code.as_u16(), 301 | 302 | 307 | 308) => code,
+                _ => return Err(Error::IllegalStatus { from, status }),
+            };
+            match from.strip_suffix('*') {
+                Some(prefix) => redirects.prefixes.push((prefix.into(), Redirect { to, status })),
+                None => {
+                    redirects.exact.insert(from, Redirect { to, status });
+                },
+            }
+        }
+        redirects.prefixes.sort_by(|(lhs, _), (rhs, _)| rhs.len().cmp(&lhs.len()));
+        Ok(redirects)
+    }
+
+    /// Returns the number of redirects in this map.
+    ///
+    /// # Returns
+    /// The number of exact and prefix redirects combined.
+    #[inline]
+    pub fn len(&self) -> usize { self.exact.len() + self.prefixes.len() }
+
+    /// Returns whether there are any redirects in this map.
+    ///
+    /// # Returns
+    /// True if there are none, false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.exact.is_empty() && self.prefixes.is_empty() }
+
+    /// Finds where a request should be redirected to, if anywhere.
+    ///
+    /// Exact redirects take precedence over prefix ones, and longer prefixes over shorter ones.
+    ///
+    /// # Arguments
+    /// - `path`: The (raw) path of the request.
+    /// - `query`: The query of the request, if any, which is kept unless the target has its own.
+    ///
+    /// # Returns
+    /// The status code and `Location` to redirect with, or [`None`] if the path isn't redirected.
+    pub fn lookup(&self, path: &str, query: Option<&str>) -> Option<(StatusCode, String)> {
+        let (redirect, location): (&Redirect, String) = match self.exact.get(path) {
+            Some(redirect) => (redirect, redirect.to.clone()),
+            None => {
+                let (redirect, rest): (&Redirect, &str) =
+                    self.prefixes.iter().find_map(|(prefix, redirect)| path.strip_prefix(prefix.as_str()).map(|rest| (redirect, rest)))?;
+                match redirect.to.strip_suffix('*') {
+                    Some(to) => (redirect, format!("{to}{rest}")),
+                    None => (redirect, redirect.to.clone()),
+                }
+            },
+        };
+        match query {
+            Some(query) if !location.contains('?') => Some((redirect.status, format!("{location}?{query}"))),
+            _ => Some((redirect.status, location)),
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//  SERVER.rs
+//    by Lut99
+//
+//  Created:
+//    14 Oct 2026, 12:44:54
+//  Last edited:
+//    17 Oct 2026, 10:09:42
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Assembles the paths into a [`Router`] and serves it, such that the
+//!   static host can also be embedded in other applications.
+//
+
+use std::convert::Infallible;
+use std::error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
+use axum::extract::Request;
+use axum::middleware;
+use axum::routing::get;
+use axum::Router;
+use error_trace::trace;
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder as HyperBuilder;
+use log::{debug, error, info, warn};
+use tokio::net::{TcpListener, TcpStream};
+use tower_service::Service as _;
+
+use crate::{access, admin, guards, info, livereload, sri, stats};
+use crate::logging::{PROXY, RATELIMIT, SERVER};
+use crate::proxy::{self, Header};
+use crate::state::{Context, HttpVersion, IpLimiter, IpPermit, ProxyProtocol};
+use crate::stats::{ConnectionGuard, ConnectionId};
+use crate::www;
+
+
+/***** CONSTANTS *****/
+/// The time between two checks whether the readiness file exists.
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors thrown while serving.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to bind the server to the given address.
+    Bind { addr: SocketAddr, err: std::io::Error },
+    /// Failed to accept an incoming connection.
+    Accept { err: std::io::Error },
+}
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            Bind { addr, .. } => write!(f, "Failed to bind server to '{addr}'"),
+            Accept { .. } => write!(f, "Failed to accept incoming connection"),
+        }
+    }
+}
+impl error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use Error::*;
+        match self {
+            Bind { err, .. } => Some(err),
+            Accept { err } => Some(err),
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Builds the [`Router`] that hosts the static website.
+///
+/// This can be used to [`Router::nest()`] the static host under the routes of another application.
+///
+/// # Arguments
+/// - `state`: The shared [`Context`] that configures the paths.
+///
+/// # Returns
+/// A [`Router`] serving the site under `/`, plus any enabled auxiliary paths.
+pub fn router(state: Arc<Context>) -> Router {
+    let mut router: Router<Arc<Context>> = Router::new();
+
+    // Add the auxiliary paths first, so they have precedence over site files
+    if !state.version_path.is_empty() {
+        router = router.route(&state.version_path, get(info::version).options(info::options));
+    }
+    if let Some(sri) = &state.sri_manifest {
+        router = router.route(&sri.path, get(sri::manifest).options(info::options));
+    }
+    if let Some(livereload) = &state.livereload {
+        router = router.route(&livereload.path, get(livereload::events).options(info::options));
+    }
+    if let Some(api) = &state.admin {
+        router = router.route(&format!("{}/connections", api.path), get(admin::connections).options(info::options));
+        router = router.route(&format!("{}/stats", api.path), get(admin::stats).options(info::options));
+    }
+    if let Some(acme) = &state.acme_challenge {
+        router = router.route(&format!("{}/:token", acme.path), get(www::handle_acme_challenge).options(info::options));
+    }
+
+    // Then add the site itself
+    router
+        .route("/", get(www::handle).options(www::handle_options).fallback(www::handle_alias))
+        .route("/*path", get(www::handle).options(www::handle_options).fallback(www::handle_alias))
+        .layer(middleware::from_fn_with_state(state.clone(), guards::validate_host))
+        .layer(middleware::from_fn_with_state(state.clone(), guards::reject_proxy_requests))
+        .layer(middleware::from_fn_with_state(state.clone(), guards::reject_trace))
+        .layer(middleware::from_fn_with_state(state.clone(), guards::server_options))
+        .layer(middleware::from_fn_with_state(state.clone(), guards::limit_response_headers))
+        .layer(middleware::from_fn_with_state(state.clone(), guards::enforce_daily_quota))
+        .layer(middleware::from_fn_with_state(state.clone(), guards::shed_overload))
+        .layer(middleware::from_fn_with_state(state.clone(), stats::record))
+        .layer(middleware::from_fn_with_state(state.clone(), access::log))
+        .layer(middleware::from_fn_with_state(state.clone(), guards::strip_response_headers))
+        .with_state(state)
+}
+
+/// Binds a server on the given address and serves the static host on it.
+///
+/// The server only starts accepting connections after the configured `startup_delay_seconds` and once the `readiness_file` exists (if any),
+/// though the address is bound immediately.
+///
+/// # Arguments
+/// - `addr`: The address to bind the server on.
+/// - `state`: The shared [`Context`] that configures the server.
+///
+/// # Returns
+/// This function never returns unless it errors. Drop the future to stop the server.
+///
+/// # Errors
+/// This function errors if we failed to bind the server or to accept a new connection.
+pub async fn serve(addr: SocketAddr, state: Arc<Context>) -> Result<Infallible, Error> {
+    // Bind the TCP Listener
+    debug!(target: SERVER, "Binding server on '{addr}'...");
+    let listener: TcpListener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(err) => return Err(Error::Bind { addr, err }),
+    };
+
+    // Hold off on accepting (the port stays reserved in the meantime) if told to do so
+    if state.startup_delay_seconds > 0 {
+        info!(target: SERVER, "Waiting {}s before accepting connections...", state.startup_delay_seconds);
+        tokio::time::sleep(Duration::from_secs(state.startup_delay_seconds)).await;
+    }
+    if let Some(path) = &state.readiness_file {
+        if !path.exists() {
+            info!(target: SERVER, "Waiting for readiness file '{}' before accepting connections...", path.display());
+            while !path.exists() {
+                tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+            }
+        }
+    }
+    serve_on(listener, state).await
+}
+
+/// Serves the static host on an already bound listener.
+///
+/// This is useful to bind on an ephemeral port (e.g., `127.0.0.1:0`) and find the address that was actually bound before serving.
+///
+/// # Arguments
+/// - `listener`: The [`TcpListener`] to accept connections on.
+/// - `state`: The shared [`Context`] that configures the server.
+///
+/// # Returns
+/// This function never returns unless it errors. Drop the future to stop the server.
+///
+/// # Errors
+/// This function errors if we failed to accept a new connection.
+pub async fn serve_on(listener: TcpListener, state: Arc<Context>) -> Result<Infallible, Error> {
+    // Build the paths
+    let router: IntoMakeServiceWithConnectInfo<Router, SocketAddr> = router(state.clone()).into_make_service_with_connect_info();
+
+    // Accept new connections!
+    info!(target: SERVER, "Initialization OK, awaiting connections...");
+    loop {
+        // Accept a new connection
+        let (mut socket, remote_addr): (TcpStream, SocketAddr) = match listener.accept().await {
+            Ok(res) => res,
+            Err(err) => return Err(Error::Accept { err }),
+        };
+
+        // Move the rest to a separate task
+        let router: IntoMakeServiceWithConnectInfo<_, _> = router.clone();
+        let mode: ProxyProtocol = state.proxy_protocol;
+        let proxy_timeout: Duration = Duration::from_millis(state.proxy_header_timeout_ms);
+        let http1: bool = state.http_versions.contains(&HttpVersion::Http1);
+        let http2: bool = state.http_versions.contains(&HttpVersion::Http2);
+        let keep_alive: bool = state.keep_alive;
+        let ip_limiter: Option<Arc<IpLimiter>> = state.ip_limiter.clone();
+        let connection: ConnectionGuard = state.stats.connection(remote_addr);
+        tokio::spawn(async move {
+            // Count the connection as active for as long as this task lives
+            let connection: ConnectionGuard = connection;
+
+            // Find the real client address if we're behind a load balancer
+            // NOTE: Clients that never send anything would keep the connection open forever (and uncounted by the `ip_limiter`) otherwise
+            let remote_addr: SocketAddr = if mode != ProxyProtocol::Off {
+                let Ok(header) = tokio::time::timeout(proxy_timeout, proxy::read_header(&mut socket)).await else {
+                    warn!(
+                        target: PROXY,
+                        "Dropping connection from '{remote_addr}' that did not send a PROXY protocol header within {}ms",
+                        proxy_timeout.as_millis()
+                    );
+                    return;
+                };
+                match header {
+                    Ok(Header::Proxied(addr)) => {
+                        debug!(target: PROXY, "Connection from '{remote_addr}' is proxied for '{addr}'");
+                        addr
+                    },
+                    Ok(Header::Local) => remote_addr,
+                    Ok(Header::Missing) if mode == ProxyProtocol::Optional => remote_addr,
+                    Ok(Header::Missing) => {
+                        warn!(target: PROXY, "Dropping connection from '{remote_addr}' that did not start with a PROXY protocol header");
+                        return;
+                    },
+                    Err(err) => {
+                        warn!(
+                            target: PROXY,
+                            "{}",
+                            trace!(("Failed to read PROXY protocol header of connection from '{remote_addr}'"), err)
+                        );
+                        return;
+                    },
+                }
+            } else {
+                remote_addr
+            };
+
+            // Enforce the connection limit of the (real) client
+            let _permit: Option<IpPermit> = match ip_limiter.map(|limiter| limiter.try_acquire(remote_addr.ip())) {
+                Some(Some(permit)) => Some(permit),
+                Some(None) => {
+                    warn!(target: RATELIMIT, "Dropping connection from '{remote_addr}' because it has too many open connections");
+                    return;
+                },
+                None => None,
+            };
+            debug!(target: SERVER, "Handling incoming connection from '{remote_addr}'");
+            connection.set_remote(remote_addr);
+            let id: ConnectionId = connection.id();
+
+            // Build  the service
+            let service = hyper::service::service_fn(|mut request: Request<Incoming>| {
+                // Let the stats know on which connection the request arrived
+                request.extensions_mut().insert(id);
+                // Sadly, we must `move` again because this service could be called multiple times (at least according to the typesystem)
+                let mut router = router.clone();
+                async move {
+                    // SAFETY: We can call `unwrap()` because the call returns an infallible.
+                    router.call(remote_addr).await.unwrap().call(request).await
+                }
+            });
+
+            // Create a service that handles this for us
+            let socket: TokioIo<_> = TokioIo::new(socket);
+            let mut builder: HyperBuilder<TokioExecutor> = match (http1, http2) {
+                (true, false) => HyperBuilder::new(TokioExecutor::new()).http1_only(),
+                (false, true) => HyperBuilder::new(TokioExecutor::new()).http2_only(),
+                _ => HyperBuilder::new(TokioExecutor::new()),
+            };
+            // NOTE: This makes hyper send `Connection: close` and close the connection after the first response; HTTP/2 has no equivalent
+            if !keep_alive {
+                builder.http1().keep_alive(false);
+            }
+            if let Err(err) = builder.serve_connection_with_upgrades(socket, service).await {
+                error!(target: SERVER, "{}", trace!(("Failed to serve incoming connection"), *err));
+            }
+        });
+    }
+}
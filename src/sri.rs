@@ -0,0 +1,205 @@
+//  SRI.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 09:23:42
+//  Last edited:
+//    17 Oct 2026, 02:39:54
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a manifest of Subresource Integrity (SRI) hashes for the
+//!   assets of the site, such that build or templating steps can fetch
+//!   the current ones.
+//
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse as _, Response};
+use axum::Json;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use error_trace::trace;
+use log::{debug, error, warn};
+use sha2::{Digest as _, Sha384};
+
+use crate::logging::SRI;
+use crate::state::{Context, SriManifest};
+use crate::www;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Decides whether a file may be listed in the manifest, which it may only if it's served to everyone as-is.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that defines which files are protected, signed, gone or sent when something isn't found.
+/// - `rel`: The path of the file, relative to the site root.
+/// - `canonical`: The canonical path of the file.
+///
+/// # Returns
+/// False if the file is protected, requires a signed URL, is gone, or is the gone or a not found file; true otherwise.
+fn is_listable(state: &Context, rel: &Path, canonical: &Path) -> bool {
+    if state.protected.contains(canonical)
+        || state.signed_urls.as_ref().is_some_and(|signed| signed.matcher.find(rel).is_some())
+        || state.gone_matcher.find(rel).is_some()
+        || state.gone_file.as_deref() == Some(canonical)
+    {
+        return false;
+    }
+    let overrides = state.not_found_overrides.values().map(PathBuf::as_path);
+    let mut not_found_files = state.not_found_file.files().map(|(_, path)| path).chain(overrides);
+    !not_found_files.any(|path| fs::canonicalize(path).is_ok_and(|path| path == canonical))
+}
+
+/// Recursively collects the files in a directory that should be listed in the manifest.
+///
+/// Symlinked directories are not followed, and symlinked files only if they resolve to within the site directory.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that defines the site directory and which files may be listed (see [`is_listable()`]).
+/// - `sri`: The [`SriManifest`] that defines which extensions are listed.
+/// - `dir`: The directory to collect the files of.
+/// - `files`: The list to push the paths of the files to.
+fn collect(state: &Context, sri: &SriManifest, dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries: fs::ReadDir = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            warn!(target: SRI, "{}", trace!(("Failed to read directory '{}' for SRI manifest", dir.display()), err));
+            return;
+        },
+    };
+    for entry in entries.flatten() {
+        let path: PathBuf = entry.path();
+        match entry.file_type() {
+            Ok(ty) if ty.is_dir() => collect(state, sri, &path, files),
+            Ok(_) => {
+                if !path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| sri.extensions.iter().any(|allowed| allowed == ext)) {
+                    continue;
+                }
+                let Ok(canonical) = path.canonicalize() else { continue };
+                let Ok(rel) = path.strip_prefix(&state.site) else { continue };
+                if canonical.starts_with(&state.site) && canonical.is_file() && is_listable(state, rel, &canonical) {
+                    files.push(path);
+                }
+            },
+            Err(_) => continue,
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Caches the SRI hashes of files, which are reused as long as their modification time and length don't change.
+#[derive(Debug, Default)]
+pub struct SriCache {
+    /// The hashes by path, together with the modification time and length of the file they were computed for.
+    hashes: Mutex<HashMap<PathBuf, (Option<SystemTime>, u64, String)>>,
+}
+impl SriCache {
+    /// Returns the SRI hash of a file, computing it only if it changed since it was last asked for.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to hash.
+    ///
+    /// # Returns
+    /// The `sha384-<base64>` integrity value of the file.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the file.
+    pub fn hash(&self, path: &Path) -> Result<String, std::io::Error> {
+        let md: fs::Metadata = fs::metadata(path)?;
+        let (modified, len): (Option<SystemTime>, u64) = (md.modified().ok(), md.len());
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        if let Some((cached_modified, cached_len, hash)) = self.hashes.lock().unwrap().get(path) {
+            if *cached_modified == modified && modified.is_some() && *cached_len == len {
+                return Ok(hash.clone());
+            }
+        }
+
+        // NOTE: We don't hold the lock while hashing, so other requests aren't blocked on large files
+        let mut hasher: Sha384 = Sha384::new();
+        std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+        let hash: String = format!("sha384-{}", BASE64.encode(hasher.finalize()));
+        self.hashes.lock().unwrap().insert(path.into(), (modified, len, hash.clone()));
+        Ok(hash)
+    }
+
+    /// Forgets the hashes of all files but the given ones, e.g., because the others were removed.
+    ///
+    /// # Arguments
+    /// - `keep`: The paths of the files whose hashes to keep.
+    pub fn retain(&self, keep: &[PathBuf]) {
+        let keep: HashSet<&PathBuf> = keep.iter().collect();
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        self.hashes.lock().unwrap().retain(|path, _| keep.contains(path));
+    }
+}
+
+
+
+/// Computes the SRI manifest of the site.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that defines the site directory.
+/// - `sri`: The [`SriManifest`] that defines which files are listed and caches their hashes.
+///
+/// # Returns
+/// A map of request paths (e.g., `/js/app.js`) to their integrity values (e.g., `sha384-...`). Files that cannot be read are left out.
+pub fn compute(state: &Context, sri: &SriManifest) -> BTreeMap<String, String> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    collect(state, sri, &state.site, &mut files);
+    sri.cache.retain(&files);
+
+    let mut manifest: BTreeMap<String, String> = BTreeMap::new();
+    for path in files {
+        let Ok(rel) = path.strip_prefix(&state.site) else { continue };
+        let Some(rel) = rel.iter().map(|comp| comp.to_str()).collect::<Option<Vec<&str>>>() else {
+            warn!(target: SRI, "Not listing file '{}' with non-UTF-8 path in SRI manifest", path.display());
+            continue;
+        };
+        match sri.cache.hash(&path) {
+            Ok(hash) => {
+                manifest.insert(format!("/{}", rel.join("/")), hash);
+            },
+            Err(err) => warn!(target: SRI, "{}", trace!(("Failed to hash file '{}' for SRI manifest", path.display()), err)),
+        }
+    }
+    manifest
+}
+
+/// Serves the SRI manifest of the site as JSON.
+///
+/// Hashes are computed when they're first asked for, and then reused until the file changes.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+///
+/// # Returns
+/// 200 OK with a map of request paths to their integrity values as JSON, or 404 NOT FOUND if the manifest is disabled.
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn manifest(State(state): State<Arc<Context>>) -> Response {
+    debug!(target: SRI, "Handling GET SRI manifest");
+    if state.sri_manifest.is_none() {
+        return www::return_error(&state, StatusCode::NOT_FOUND).into_response();
+    }
+
+    // Walking and hashing the site blocks, so do it on a thread that may
+    let worker: Arc<Context> = state.clone();
+    match tokio::task::spawn_blocking(move || worker.sri_manifest.as_ref().map(|sri| compute(&worker, sri)).unwrap_or_default()).await {
+        Ok(manifest) => Json(manifest).into_response(),
+        Err(err) => {
+            error!(target: SRI, "{}", trace!(("Failed to compute SRI manifest"), err));
+            www::return_error(&state, StatusCode::INTERNAL_SERVER_ERROR).into_response()
+        },
+    }
+}
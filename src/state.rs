@@ -4,7 +4,7 @@
 //  Created:
 //    17 Jul 2024, 19:03:23
 //  Last edited:
-//    17 Jul 2024, 22:29:57
+//    17 Oct 2026, 11:28:00
 //  Auto updated?
 //    Yes
 //
@@ -12,14 +12,30 @@
 //!   Represents runtime state shared by paths.
 //
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs::File;
 use std::io::ErrorKind;
-use std::path::{Path, PathBuf};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 use std::{error, fs};
 
-use log::{debug, info, warn};
-use serde::{Deserialize, Serialize};
+use hyper::header::{self, HeaderMap, HeaderName, HeaderValue, InvalidHeaderName, InvalidHeaderValue};
+use hyper::{Method, StatusCode};
+use log::{debug, info, warn, Level};
+use regex::Regex;
+use serde::{Deserialize, Serialize, Serializer};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::livereload::SiteWatcher;
+use crate::logging::{CONFIG, RATELIMIT};
+use crate::matcher::PathMatcher;
+use crate::redirects::{self, Redirects};
+use crate::sri::SriCache;
+use crate::stats::Stats;
 
 
 /***** CONSTANTS *****/
@@ -40,6 +56,156 @@ const DEFAULT_NOT_FOUND_FILE: &'static str = r#"
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Returns the default charset appended to textual content types.
+#[inline]
+fn default_charset() -> String { "utf-8".into() }
+
+/// Serializes a secret as `<redacted>`, such that it never ends up in the output of `--print-config`.
+///
+/// # Arguments
+/// - `_secret`: The secret to leave out.
+/// - `serializer`: The [`Serializer`] to serialize the placeholder with.
+///
+/// # Returns
+/// Whatever the `serializer` returns.
+#[inline]
+fn redact<S: Serializer>(_secret: &str, serializer: S) -> Result<S::Ok, S::Error> { serializer.serialize_str("<redacted>") }
+
+/// Checks whether the given path can be used as a fixed route in the router.
+///
+/// # Arguments
+/// - `path`: The path to check.
+///
+/// # Returns
+/// True if it starts with a `/`, isn't just the root and doesn't contain any routing syntax (`*` or `:`), false otherwise.
+#[inline]
+fn is_valid_route(path: &str) -> bool { path.len() > 1 && path.starts_with('/') && !path.contains(['*', ':']) }
+
+/// Returns true, for use as a serde default.
+#[inline]
+fn default_true() -> bool { true }
+
+/// Returns the default number of seconds after which clients may retry when the server is unavailable.
+#[inline]
+fn default_retry_after_seconds() -> u64 { 60 }
+
+/// Returns the default order in which request paths are resolved to files.
+#[inline]
+fn default_resolution_order() -> Vec<Resolution> { vec![Resolution::Exact, Resolution::DirIndex] }
+
+/// Returns the default files that are served when a directory is requested.
+#[inline]
+fn default_index_files() -> Vec<String> { vec!["index.html".into()] }
+
+/// Checks whether the given filename can be used as an index file.
+///
+/// # Arguments
+/// - `name`: The filename to check.
+///
+/// # Returns
+/// True if it is a plain filename (i.e., no separators, `.` or `..`), false otherwise.
+#[inline]
+fn is_valid_index_file(name: &str) -> bool { !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\']) }
+
+/// Returns the default HTTP versions that the server speaks.
+#[inline]
+fn default_http_versions() -> Vec<HttpVersion> { vec![HttpVersion::Http1, HttpVersion::Http2] }
+
+/// Returns the default maximum length of request paths, in bytes.
+#[inline]
+fn default_max_path_length() -> usize { 4096 }
+
+/// Returns the default maximum number of components in request paths.
+#[inline]
+fn default_max_path_components() -> usize { 128 }
+
+/// Returns the default path under which ACME HTTP-01 challenges are requested.
+#[inline]
+fn default_acme_challenge_path() -> String { "/.well-known/acme-challenge".into() }
+
+/// Returns the default path under which the admin API is served.
+#[inline]
+fn default_admin_path() -> String { "/_admin".into() }
+
+/// Returns the default maximum size of files that are transformed while serving them.
+#[inline]
+fn default_transform_max_size() -> u64 { 1024 * 1024 }
+
+/// Returns the default relation of preloaded assets.
+#[inline]
+fn default_preload_rel() -> String { "preload".into() }
+
+/// Returns the default placeholder that is replaced by CSP nonces.
+#[inline]
+fn default_csp_nonce_placeholder() -> String { "{{csp_nonce}}".into() }
+
+/// Returns the default path on which the SRI manifest is served.
+#[inline]
+fn default_sri_manifest_path() -> String { "/_integrity.json".into() }
+
+/// Returns the default extensions of the files listed in the SRI manifest.
+#[inline]
+fn default_sri_manifest_extensions() -> Vec<String> { vec!["css".into(), "js".into(), "mjs".into()] }
+
+/// Returns the default path on which live-reload events are served.
+#[inline]
+fn default_livereload_path() -> String { "/_livereload".into() }
+
+/// Returns the default number of milliseconds between two checks of the site directory for live-reloading.
+#[inline]
+fn default_livereload_poll_interval_ms() -> u64 { 500 }
+
+/// Returns the default number of milliseconds that clients get to send their PROXY protocol header.
+#[inline]
+fn default_proxy_header_timeout_ms() -> u64 { 5000 }
+
+/// Returns the default status code with which `TRACE`-requests are refused.
+#[inline]
+fn default_trace_status() -> u16 { 405 }
+
+/// Returns the default status code with which `OPTIONS *` is answered.
+#[inline]
+fn default_options_status() -> u16 { 204 }
+
+/// Returns the default maximum number of headers in a response.
+#[inline]
+fn default_max_response_headers() -> usize { 64 }
+
+/// Returns the default maximum total size of the headers in a response, in bytes.
+#[inline]
+fn default_max_response_header_bytes() -> usize { 16 * 1024 }
+
+/// Returns the default status code with which the not found file is sent.
+#[inline]
+fn default_not_found_status() -> u16 { 404 }
+
+/// Computes the size of the given headers as they would be sent in HTTP/1.1.
+///
+/// # Arguments
+/// - `headers`: The headers to compute the size of.
+///
+/// # Returns
+/// The total number of bytes of the names and values, including the `: ` and CRLF of every header.
+#[inline]
+pub fn header_bytes(headers: &HeaderMap) -> usize { headers.iter().map(|(name, value)| name.as_str().len() + value.len() + 4).sum() }
+
+/// Checks whether the given charset can be safely used in a `Content-Type` header.
+///
+/// # Arguments
+/// - `charset`: The charset to check.
+///
+/// # Returns
+/// True if it only consists of characters that may appear in a charset name, false otherwise.
+#[inline]
+fn is_valid_charset(charset: &str) -> bool {
+    !charset.is_empty() && charset.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+
+
+
+
 /***** ERRORS *****/
 /// Defines errors thrown by the [`Context`].
 #[derive(Debug)]
@@ -48,6 +214,86 @@ pub enum Error {
     ConfigOpen { path: PathBuf, err: std::io::Error },
     /// Failed to read & parse the target config file.
     ConfigParse { path: PathBuf, err: serde_yml::Error },
+    /// Failed to load the redirect file.
+    Redirects { err: redirects::Error },
+    /// No HTTP versions were enabled.
+    IllegalHttpVersions,
+    /// No resolution steps were configured.
+    IllegalResolutionOrder,
+    /// The configured root file is not a relative path within the site directory.
+    IllegalRootFile { path: PathBuf },
+    /// The configured not found headers exceed the maximum number or size of response headers.
+    IllegalNotFoundHeaders { count: usize, bytes: usize },
+    /// The name and version of the server do not form a valid `Server`-header.
+    IllegalServerHeader { name: &'static str, version: &'static str, err: InvalidHeaderValue },
+    /// A configured index file is not a plain filename.
+    IllegalIndexFile { name: String },
+    /// A configured MIME type is not valid.
+    IllegalMimeType { ext: String, mime: String },
+    /// A configured charset contained illegal characters.
+    IllegalCharset { charset: String },
+    /// The configured not found status is not a valid status code.
+    IllegalNotFoundStatus { code: u16 },
+    /// The name of a configured not found header is not valid.
+    IllegalNotFoundHeaderName { name: String, err: InvalidHeaderName },
+    /// The value of a configured not found header is not valid.
+    IllegalNotFoundHeaderValue { name: String, err: InvalidHeaderValue },
+    /// The name of a header to strip from responses is not valid.
+    IllegalStrippedHeaderName { name: String, err: InvalidHeaderName },
+    /// A header to strip from responses is written by the HTTP implementation itself, so it cannot be stripped.
+    UnstrippableHeader { name: String },
+    /// A method to alias as GET is not a valid method.
+    IllegalGetAliasMethod { method: String },
+    /// The maximum number of concurrent reads is zero.
+    IllegalMaxConcurrentReads,
+    /// The maximum number of connections per IP is zero.
+    IllegalMaxConnectionsPerIp,
+    /// A configured bandwidth limit is zero.
+    IllegalBandwidthLimit { pattern: String },
+    /// The daily quota per IP is zero.
+    IllegalDailyQuota,
+    /// A field of the overload protection is zero.
+    IllegalOverloadProtection { field: &'static str },
+    /// The maximum artificial delay is smaller than the minimum one.
+    IllegalArtificialDelay { min_ms: u64, max_ms: u64 },
+    /// The name of a request header to log is not valid.
+    IllegalLogHeader { name: String, err: InvalidHeaderName },
+    /// Failed to generate the key with which client IPs are hashed in the access log.
+    AccessLogKey { err: getrandom::Error },
+    /// The configured status code for blocked files is not 403 or 404.
+    IllegalBlockedStatus { code: u16 },
+    /// The configured status code for `OPTIONS *` is not 200 or 204.
+    IllegalOptionsStatus { code: u16 },
+    /// The configured status code for `TRACE`-requests is not 403 or 405.
+    IllegalTraceStatus { code: u16 },
+    /// The configured version path is not a valid path.
+    IllegalVersionPath { path: String },
+    /// The configured admin API path is not a valid path.
+    IllegalAdminPath { path: String },
+    /// The configured admin API token is empty.
+    EmptyAdminToken,
+    /// The configured secret for signed URLs is empty.
+    EmptySignedUrlSecret,
+    /// The placeholder for CSP nonces is empty.
+    EmptyCspNoncePlaceholder,
+    /// The CSP policy is not a valid header value.
+    IllegalCspPolicy { policy: String },
+    /// The configured ACME challenge path is not a valid path.
+    IllegalAcmeChallengePath { path: String },
+    /// The configured SRI manifest path is not a valid path.
+    IllegalSriManifestPath { path: String },
+    /// The configured live-reload path is not a valid path.
+    IllegalLiveReloadPath { path: String },
+    /// The configured live-reload poll interval is zero.
+    IllegalLiveReloadInterval,
+    /// The configured PROXY protocol header timeout is zero.
+    IllegalProxyHeaderTimeout,
+    /// The configured fingerprint pattern is not a valid regular expression.
+    IllegalFingerprintPattern { pattern: String, err: regex::Error },
+    /// A preload entry is malformed.
+    IllegalPreload { pattern: String, href: String, reason: &'static str },
+    /// The not found files are given per MIME type, but none is given for `text/html`.
+    MissingHtmlNotFoundFile,
 
     /// Failed to create a default config file.
     ConfigCreate { path: PathBuf, err: std::io::Error },
@@ -55,10 +301,26 @@ pub enum Error {
     ConfigWrite { path: PathBuf, err: serde_yml::Error },
     /// Failed to create a default not found file.
     NotFoundFileCreate { path: PathBuf, err: std::io::Error },
+    /// The not found file does not exist, and we're not allowed to create it.
+    NotFoundFileMissing { path: PathBuf },
+    /// The not found file for a non-HTML MIME type does not exist (these are never created).
+    NotFoundVariantMissing { mime: String, path: PathBuf },
+    /// The not found file for a path prefix does not exist within the site directory.
+    NotFoundOverrideMissing { prefix: String, path: PathBuf, err: Option<std::io::Error> },
     /// Failed to canonicalize the site directory path.
     SiteDirCanonicalize { path: PathBuf, err: std::io::Error },
     /// Failed to create the site directory.
     SiteDirCreate { path: PathBuf, err: std::io::Error },
+    /// The site directory does not exist, and we're not allowed to create it.
+    SiteDirMissing { path: PathBuf },
+    /// The single file to serve for everything does not exist (or is outside of the site directory).
+    SingleFileMissing { path: PathBuf, err: Option<std::io::Error> },
+    /// The page for removed content does not exist within the site directory.
+    GoneFileMissing { path: PathBuf, err: Option<std::io::Error> },
+    /// A configured bot file does not exist within the site.
+    BotFileMissing { pattern: String, path: PathBuf, err: Option<std::io::Error> },
+    /// A bot user agent is empty, which would match every client.
+    EmptyBotUserAgent,
 }
 impl Display for Error {
     #[inline]
@@ -67,12 +329,81 @@ impl Display for Error {
         match self {
             ConfigOpen { path, .. } => write!(f, "Failed to open config file '{}'", path.display()),
             ConfigParse { path, .. } => write!(f, "Failed to read & parse config file '{}'", path.display()),
+            Redirects { .. } => write!(f, "Failed to load redirects"),
+            IllegalHttpVersions => write!(f, "At least one HTTP version must be enabled in 'http_versions'"),
+            IllegalResolutionOrder => write!(f, "At least one resolution step must be given in 'resolution_order'"),
+            IllegalRootFile { path } => write!(f, "Illegal root file '{}' (must be a relative path without '..')", path.display()),
+            IllegalNotFoundHeaders { count, bytes } => write!(
+                f,
+                "Not found headers ({count} header(s), {bytes} byte(s)) exceed `max_response_headers` or `max_response_header_bytes`"
+            ),
+            IllegalServerHeader { name, version, .. } => write!(f, "Illegal server name '{name}' or version '{version}' for the Server-header"),
+            IllegalIndexFile { name } => write!(f, "Illegal index file '{name}' (must be a plain filename)"),
+            IllegalMimeType { ext, mime } => write!(f, "Illegal MIME type '{mime}' for extension '{ext}'"),
+            IllegalCharset { charset } => {
+                write!(f, "Illegal charset '{charset}' (may only contain ASCII alphanumeric characters, '-', '_', '.' or ':')")
+            },
+            IllegalNotFoundStatus { code } => write!(f, "Illegal not found status code {code}"),
+            IllegalNotFoundHeaderName { name, .. } => write!(f, "Illegal not found header name '{name}'"),
+            IllegalStrippedHeaderName { name, .. } => write!(f, "Illegal name '{name}' of response header to strip"),
+            UnstrippableHeader { name } => {
+                write!(f, "Response header '{name}' is written by the HTTP implementation itself, and cannot be stripped")
+            },
+            IllegalNotFoundHeaderValue { name, .. } => write!(f, "Illegal value for not found header '{name}'"),
+            IllegalGetAliasMethod { method } => write!(f, "Illegal method '{method}' to alias as GET"),
+            IllegalMaxConcurrentReads => write!(f, "Illegal maximum number of concurrent reads 0 (would never read any file)"),
+            IllegalMaxConnectionsPerIp => write!(f, "Illegal maximum number of connections per IP 0 (would never accept any connection)"),
+            IllegalBandwidthLimit { pattern } => write!(f, "Illegal bandwidth limit 0 for '{pattern}' (would never send anything)"),
+            IllegalDailyQuota => write!(f, "Illegal daily quota 0 (would never send anything)"),
+            IllegalOverloadProtection { field } => write!(f, "Illegal overload protection '{field}' 0 (must be positive)"),
+            IllegalArtificialDelay { min_ms, max_ms } => {
+                write!(f, "Illegal artificial delay of {min_ms} to {max_ms} milliseconds (maximum must be at least the minimum)")
+            },
+            IllegalLogHeader { name, .. } => write!(f, "Illegal name '{name}' of request header to log"),
+            AccessLogKey { .. } => write!(f, "Failed to generate key for hashing client IPs in the access log"),
+            IllegalBlockedStatus { code } => write!(f, "Illegal status code {code} for blocked files (must be 403 or 404)"),
+            IllegalOptionsStatus { code } => write!(f, "Illegal status code {code} for OPTIONS * (must be 200 or 204)"),
+            IllegalTraceStatus { code } => write!(f, "Illegal status code {code} for TRACE-requests (must be 403 or 405)"),
+            IllegalVersionPath { path } => write!(f, "Illegal version path '{path}' (must start with '/' and may not be '/' itself or contain '*' or ':')"),
+            IllegalAdminPath { path } => {
+                write!(f, "Illegal admin API path '{path}' (must start with '/' and may not be '/' itself or contain '*' or ':')")
+            },
+            EmptyAdminToken => write!(f, "Admin API token may not be empty"),
+            EmptySignedUrlSecret => write!(f, "Signed URL secret may not be empty"),
+            EmptyCspNoncePlaceholder => write!(f, "CSP nonce placeholder may not be empty"),
+            IllegalCspPolicy { policy } => write!(f, "Illegal CSP policy '{policy}'"),
+            IllegalAcmeChallengePath { path } => {
+                write!(f, "Illegal ACME challenge path '{path}' (must start with '/' and may not be '/' itself or contain '*' or ':')")
+            },
+            IllegalSriManifestPath { path } => {
+                write!(f, "Illegal SRI manifest path '{path}' (must start with '/' and may not be '/' itself or contain '*' or ':')")
+            },
+            IllegalLiveReloadPath { path } => {
+                write!(f, "Illegal live-reload path '{path}' (must start with '/' and may not be '/' itself or contain '*' or ':')")
+            },
+            IllegalLiveReloadInterval => write!(f, "Illegal live-reload poll interval (must be at least 1 millisecond)"),
+            IllegalProxyHeaderTimeout => write!(f, "Illegal PROXY protocol header timeout (must be at least 1 millisecond)"),
+            IllegalFingerprintPattern { pattern, .. } => write!(f, "Illegal fingerprint pattern '{pattern}'"),
+            IllegalPreload { pattern, href, reason } => write!(f, "Illegal preload of '{href}' for '{pattern}' ({reason})"),
+            MissingHtmlNotFoundFile => write!(f, "Not found files per MIME type must include one for 'text/html' (used if nothing else matches)"),
 
             ConfigCreate { path, .. } => write!(f, "Failed to create default config file '{}'", path.display()),
             ConfigWrite { path, .. } => write!(f, "Failed to write to default config file '{}'", path.display()),
             NotFoundFileCreate { path, .. } => write!(f, "Failed to create default not found file '{}'", path.display()),
+            NotFoundFileMissing { path } => write!(f, "Not found file '{}' does not exist (and `create_missing` is false)", path.display()),
+            NotFoundVariantMissing { mime, path } => write!(f, "Not found file '{}' for '{mime}' does not exist", path.display()),
+            NotFoundOverrideMissing { prefix, path, .. } => {
+                write!(f, "Not found file '{}' for prefix '{prefix}' does not exist within the site directory", path.display())
+            },
             SiteDirCanonicalize { path, .. } => write!(f, "Failed to canonicalize site directory path '{}'", path.display()),
             SiteDirCreate { path, .. } => write!(f, "Failed to create site directory '{}'", path.display()),
+            SiteDirMissing { path } => write!(f, "Site directory '{}' does not exist (and `create_missing` is false)", path.display()),
+            SingleFileMissing { path, .. } => write!(f, "Single file '{}' does not exist within the site directory", path.display()),
+            GoneFileMissing { path, .. } => write!(f, "Gone file '{}' does not exist within the site directory", path.display()),
+            BotFileMissing { pattern, path, .. } => {
+                write!(f, "Bot file '{}' for pattern '{pattern}' does not exist within the site directory", path.display())
+            },
+            EmptyBotUserAgent => write!(f, "Bot user agents may not be empty (would match every client)"),
         }
     }
 }
@@ -83,12 +414,60 @@ impl error::Error for Error {
         match self {
             ConfigOpen { err, .. } => Some(err),
             ConfigParse { err, .. } => Some(err),
+            Redirects { err } => Some(err),
+            IllegalHttpVersions => None,
+            IllegalResolutionOrder => None,
+            IllegalRootFile { .. } => None,
+            IllegalNotFoundHeaders { .. } => None,
+            IllegalServerHeader { err, .. } => Some(err),
+            IllegalIndexFile { .. } => None,
+            IllegalMimeType { .. } => None,
+            IllegalCharset { .. } => None,
+            IllegalNotFoundStatus { .. } => None,
+            IllegalNotFoundHeaderName { err, .. } => Some(err),
+            IllegalStrippedHeaderName { err, .. } => Some(err),
+            UnstrippableHeader { .. } => None,
+            IllegalNotFoundHeaderValue { err, .. } => Some(err),
+            IllegalGetAliasMethod { .. } => None,
+            IllegalMaxConcurrentReads => None,
+            IllegalMaxConnectionsPerIp => None,
+            IllegalBandwidthLimit { .. } => None,
+            IllegalDailyQuota => None,
+            IllegalOverloadProtection { .. } => None,
+            IllegalArtificialDelay { .. } => None,
+            IllegalLogHeader { err, .. } => Some(err),
+            AccessLogKey { err } => Some(err),
+            IllegalBlockedStatus { .. } => None,
+            IllegalOptionsStatus { .. } => None,
+            IllegalTraceStatus { .. } => None,
+            IllegalVersionPath { .. } => None,
+            IllegalAdminPath { .. } => None,
+            EmptyAdminToken => None,
+            EmptySignedUrlSecret => None,
+            EmptyCspNoncePlaceholder => None,
+            IllegalCspPolicy { .. } => None,
+            IllegalAcmeChallengePath { .. } => None,
+            IllegalSriManifestPath { .. } => None,
+            IllegalLiveReloadPath { .. } => None,
+            IllegalLiveReloadInterval => None,
+            IllegalProxyHeaderTimeout => None,
+            IllegalFingerprintPattern { err, .. } => Some(err),
+            IllegalPreload { .. } => None,
+            MissingHtmlNotFoundFile => None,
 
             ConfigCreate { err, .. } => Some(err),
             ConfigWrite { err, .. } => Some(err),
             NotFoundFileCreate { err, .. } => Some(err),
+            NotFoundFileMissing { .. } => None,
+            NotFoundVariantMissing { .. } => None,
+            NotFoundOverrideMissing { err, .. } => err.as_ref().map(|err| err as &(dyn error::Error + 'static)),
             SiteDirCanonicalize { err, .. } => Some(err),
             SiteDirCreate { err, .. } => Some(err),
+            SiteDirMissing { .. } => None,
+            SingleFileMissing { err, .. } => err.as_ref().map(|err| err as &(dyn error::Error + 'static)),
+            GoneFileMissing { err, .. } => err.as_ref().map(|err| err as &(dyn error::Error + 'static)),
+            BotFileMissing { err, .. } => err.as_ref().map(|err| err as &(dyn error::Error + 'static)),
+            EmptyBotUserAgent => None,
         }
     }
 }
@@ -98,6 +477,615 @@ impl error::Error for Error {
 
 
 /***** LIBRARY *****/
+/// Defines where ACME HTTP-01 challenge files are served from.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AcmeChallenge {
+    /// The path under which challenges are requested.
+    #[serde(default = "default_acme_challenge_path")]
+    pub path: String,
+    /// The directory in which the ACME client (e.g., certbot) puts the challenge files.
+    pub dir:  PathBuf,
+}
+
+
+
+/// Defines the (read-only) admin API, which reports on the running server.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AdminApi {
+    /// The path under which the admin API is served.
+    #[serde(default = "default_admin_path")]
+    pub path:  String,
+    /// The token that requests must carry as `Authorization: Bearer <token>`.
+    #[serde(serialize_with = "redact")]
+    pub token: String,
+}
+
+
+
+/// Defines the manifest of Subresource Integrity (SRI) hashes of the site's assets.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SriManifest {
+    /// The path on which the manifest is served.
+    #[serde(default = "default_sri_manifest_path")]
+    pub path:       String,
+    /// The extensions (without leading dot) of the files that are listed in the manifest.
+    #[serde(default = "default_sri_manifest_extensions")]
+    pub extensions: Vec<String>,
+    /// The hashes computed so far, which are reused while the files don't change.
+    #[serde(skip)]
+    pub cache:      Arc<SriCache>,
+}
+
+/// Defines the (development-only) endpoint that tells browsers when the site changes, so they can reload.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LiveReload {
+    /// The path on which the Server-Sent Events are served.
+    #[serde(default = "default_livereload_path")]
+    pub path:             String,
+    /// The number of milliseconds between two checks of the site directory for changes.
+    #[serde(default = "default_livereload_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// The watcher of the site directory, which is started once the first browser connects.
+    #[serde(skip)]
+    pub watcher:          Arc<SiteWatcher>,
+}
+
+
+
+/// Defines which paths may only be accessed through signed URLs, and how those are signed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignedUrls {
+    /// The secret with which URLs are signed (see [`sign()`](crate::signed::sign())).
+    #[serde(serialize_with = "redact")]
+    pub secret:  String,
+    /// The path patterns that require a signature (see [`PathMatcher`]).
+    pub paths:   Vec<String>,
+    /// The compiled version of `paths`.
+    #[serde(skip)]
+    pub matcher: PathMatcher<()>,
+}
+
+/// Defines the `Content-Security-Policy` that is sent with HTML files, with a fresh nonce for every response.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CspNonce {
+    /// The policy to send, in which every `placeholder` is replaced by the nonce (e.g., `script-src 'nonce-{{csp_nonce}}'`).
+    pub policy:      String,
+    /// The placeholder that is replaced by the nonce, both in the policy and in the HTML files themselves.
+    #[serde(default = "default_csp_nonce_placeholder")]
+    pub placeholder: String,
+}
+
+/// Defines a single asset that HTML pages tell browsers to fetch early through a `Link`-header.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Preload {
+    /// The URL of the asset.
+    pub href: String,
+    /// The relation of the asset to the page, e.g., `preload` or `preconnect`.
+    #[serde(default = "default_preload_rel")]
+    pub rel: String,
+    /// What kind of asset it is (e.g., `style` or `script`), which is required for `preload`.
+    #[serde(default, rename = "as")]
+    pub destination: Option<String>,
+    /// Whether the asset is fetched with CORS (e.g., required for fonts).
+    #[serde(default)]
+    pub crossorigin: bool,
+}
+impl Preload {
+    /// The relations that may be given.
+    pub const RELS: [&'static str; 5] = ["preload", "modulepreload", "prefetch", "preconnect", "dns-prefetch"];
+    /// The kinds of assets that may be given.
+    pub const DESTINATIONS: [&'static str; 12] =
+        ["audio", "document", "embed", "fetch", "font", "image", "object", "script", "style", "track", "video", "worker"];
+
+    /// Checks whether this entry makes sense.
+    ///
+    /// # Returns
+    /// [`None`] if it does, or the reason why not otherwise.
+    pub fn validate(&self) -> Option<&'static str> {
+        if self.href.is_empty() || !self.href.bytes().all(|b| b.is_ascii_graphic() && !matches!(b, b'<' | b'>')) {
+            return Some("href must be a non-empty, percent-encoded URL");
+        }
+        if !Self::RELS.contains(&self.rel.as_str()) {
+            return Some("rel must be preload, modulepreload, prefetch, preconnect or dns-prefetch");
+        }
+        match &self.destination {
+            Some(dest) if !Self::DESTINATIONS.contains(&dest.as_str()) => Some("unknown as"),
+            Some(_) if matches!(self.rel.as_str(), "preconnect" | "dns-prefetch") => Some("as is meaningless for connections"),
+            None if self.rel == "preload" => Some("as is required for preload"),
+            _ => None,
+        }
+    }
+
+    /// Renders this entry as (a part of) a `Link`-header.
+    ///
+    /// # Returns
+    /// A `<href>; rel=...` link.
+    pub fn link(&self) -> String {
+        let mut link: String = format!("<{}>; rel={}", self.href, self.rel);
+        if let Some(dest) = &self.destination {
+            link.push_str(&format!("; as={dest}"));
+        }
+        if self.crossorigin {
+            link.push_str("; crossorigin");
+        }
+        link
+    }
+}
+
+/// Defines when the server sheds load because requests are taking too long.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct OverloadProtection {
+    /// The number of milliseconds after which a request that is still being handled counts as slow.
+    pub latency_ms: u64,
+    /// The number of slow requests at which new requests are refused.
+    pub trigger_count: usize,
+    /// The number of seconds for which new requests are refused once triggered.
+    pub cooldown_seconds: u64,
+}
+
+/// Defines the (testing-only) delay before responding, which simulates a slow origin.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ArtificialDelay {
+    /// The minimum number of milliseconds to wait.
+    pub min_ms:  u64,
+    /// The maximum number of milliseconds to wait, such that the delay is picked at random in between. Fixed to `min_ms` if omitted.
+    #[serde(default)]
+    pub max_ms:  Option<u64>,
+    /// The path patterns that are delayed (see [`PathMatcher`]), or every path if empty.
+    #[serde(default)]
+    pub paths:   Vec<String>,
+    /// The compiled version of `paths`.
+    #[serde(skip)]
+    pub matcher: PathMatcher<()>,
+}
+
+/// Defines the access log, which has a line for every request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AccessLog {
+    /// The names of the request headers that are logged; all others are left out.
+    #[serde(default)]
+    pub log_headers:    Vec<String>,
+    /// Whether to log a keyed hash of client IPs instead of the IPs themselves.
+    #[serde(default = "default_true")]
+    pub hash_client_ip: bool,
+    /// The compiled version of `log_headers`.
+    #[serde(skip)]
+    pub headers:        Vec<HeaderName>,
+    /// The key with which client IPs are hashed, which is generated anew for every run.
+    #[serde(skip)]
+    pub ip_key:         [u8; 32],
+}
+
+
+
+/// Defines how the server picks between language variants of index files.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LanguageNegotiation {
+    /// The languages for which `index.<lang>.html` variants are available.
+    pub languages: Vec<String>,
+    /// The language to fall back to if none of the client's languages are available.
+    #[serde(default)]
+    pub default:   Option<String>,
+}
+
+
+
+/// Defines the file(s) sent back when a file isn't found.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum NotFoundFile {
+    /// A single file that is sent to every client.
+    Single(PathBuf),
+    /// A file per MIME type (e.g., `text/html` and `application/json`), picked based on the client's `Accept`-header. The `text/html` one
+    /// is sent if none of the others is accepted.
+    ByType(BTreeMap<String, PathBuf>),
+}
+impl NotFoundFile {
+    /// Returns the file that is sent if no other is accepted.
+    ///
+    /// # Returns
+    /// The single file, or the one for `text/html`.
+    ///
+    /// # Panics
+    /// This function panics if the files are given per MIME type but none is given for `text/html`. This is checked when loading the config.
+    #[inline]
+    pub fn fallback(&self) -> &Path {
+        match self {
+            Self::Single(path) => path,
+            Self::ByType(files) => files.get("text/html").expect("No HTML not found file; this should have been checked when loading"),
+        }
+    }
+
+    /// Returns all not found files.
+    ///
+    /// # Returns
+    /// An iterator over the files and the MIME types they are sent for, which is [`None`] for a single file.
+    #[inline]
+    pub fn files(&self) -> Box<dyn Iterator<Item = (Option<&str>, &Path)> + '_> {
+        match self {
+            Self::Single(path) => Box::new(std::iter::once((None, path.as_path()))),
+            Self::ByType(files) => Box::new(files.iter().map(|(mime, path)| (Some(mime.as_str()), path.as_path()))),
+        }
+    }
+
+    /// Returns all not found files, mutably (e.g., to resolve them).
+    ///
+    /// # Returns
+    /// An iterator over the paths of the files.
+    #[inline]
+    pub fn files_mut(&mut self) -> Box<dyn Iterator<Item = &mut PathBuf> + '_> {
+        match self {
+            Self::Single(path) => Box::new(std::iter::once(path)),
+            Self::ByType(files) => Box::new(files.values_mut()),
+        }
+    }
+}
+
+
+
+/// Defines the level at which not found files are logged.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotFoundLogLevel {
+    /// Not found files aren't logged at all.
+    Off,
+    /// Not found files are logged at DEBUG-level.
+    #[default]
+    Debug,
+    /// Not found files are logged at INFO-level.
+    Info,
+    /// Not found files are logged at WARN-level.
+    Warn,
+}
+impl NotFoundLogLevel {
+    /// Returns the [`Level`] to log at.
+    ///
+    /// # Returns
+    /// The [`Level`] matching this log level, or [`None`] if not found files shouldn't be logged.
+    #[inline]
+    pub fn level(&self) -> Option<Level> {
+        match self {
+            Self::Off => None,
+            Self::Debug => Some(Level::Debug),
+            Self::Info => Some(Level::Info),
+            Self::Warn => Some(Level::Warn),
+        }
+    }
+}
+
+/// Defines the line endings to which those of textual files are normalized.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    /// Lines end in `\n` (i.e., `\r\n` becomes `\n`).
+    Lf,
+    /// Lines end in `\r\n` (i.e., a `\n` without `\r` becomes `\r\n`).
+    Crlf,
+}
+
+/// Defines what the `ETag`s of files are computed from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EtagStrategy {
+    /// The modification time and length of the file, which is fast but changes whenever the file is rewritten.
+    #[default]
+    MtimeSize,
+    /// A hash of the contents of the file, which survives rewrites with the same contents but has to read the file once per change.
+    ContentHash,
+}
+
+/// Defines how to deal with parent directory components (`..`) in request paths.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParentDirPolicy {
+    /// Parent directories are resolved logically, and paths going above the site directory are not found.
+    #[default]
+    Resolve,
+    /// Any path containing a parent directory is rejected with 400 BAD REQUEST.
+    Reject,
+}
+
+/// Defines a way in which a request path may be resolved to a file.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// The path refers to a file directly.
+    Exact,
+    /// The path refers to a file after appending `.html` to it (e.g., `/guide` to `guide.html`).
+    HtmlSuffix,
+    /// The path refers to a directory, and one of its index files is served.
+    DirIndex,
+}
+
+/// Defines whether connections are expected to start with a PROXY protocol header.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyProtocol {
+    /// No PROXY protocol headers are read.
+    #[default]
+    Off,
+    /// Connections may start with a PROXY protocol header, which is used if present.
+    Optional,
+    /// Connections must start with a PROXY protocol header, and are dropped if they don't.
+    Strict,
+}
+
+
+
+/// Defines the HTTP versions that the server may speak.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpVersion {
+    /// HTTP/1.0 and HTTP/1.1.
+    Http1,
+    /// HTTP/2 over plaintext, using prior knowledge (i.e., clients that start with the HTTP/2 connection preface).
+    Http2,
+}
+
+
+
+/// Limits the number of files that are read concurrently.
+#[derive(Debug)]
+pub struct ReadLimiter {
+    /// The permits to read a file.
+    permits: Arc<Semaphore>,
+    /// The number of reads currently waiting for a permit.
+    waiting: AtomicUsize,
+}
+impl ReadLimiter {
+    /// Constructor for the ReadLimiter.
+    ///
+    /// # Arguments
+    /// - `max`: The maximum number of files that may be read concurrently.
+    ///
+    /// # Returns
+    /// A new ReadLimiter with `max` permits available.
+    #[inline]
+    pub fn new(max: usize) -> Self { Self { permits: Arc::new(Semaphore::new(max)), waiting: AtomicUsize::new(0) } }
+
+    /// Waits until a file may be read.
+    ///
+    /// # Returns
+    /// A permit that allows reading a file until it is dropped.
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        /// Keeps track of a waiting read, even if the waiting future is dropped.
+        struct Waiting<'a>(&'a AtomicUsize);
+        impl Drop for Waiting<'_> {
+            #[inline]
+            fn drop(&mut self) { self.0.fetch_sub(1, Ordering::Relaxed); }
+        }
+
+        // Try the fast path first
+        if let Ok(permit) = self.permits.clone().try_acquire_owned() {
+            return permit;
+        }
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let _waiting: Waiting = Waiting(&self.waiting);
+        debug!(target: RATELIMIT, "Read queued (queue depth: {})", self.queue_depth());
+        // SAFETY: We can call `unwrap()` because we never close the semaphore.
+        self.permits.clone().acquire_owned().await.unwrap()
+    }
+
+    /// Returns the number of reads that are currently waiting for a permit.
+    ///
+    /// # Returns
+    /// The current queue depth.
+    #[inline]
+    pub fn queue_depth(&self) -> usize { self.waiting.load(Ordering::Relaxed) }
+}
+
+
+
+/// Limits the number of connections that are open concurrently per client IP.
+#[derive(Debug)]
+pub struct IpLimiter {
+    /// The maximum number of connections per IP.
+    max: usize,
+    /// The number of open connections per IP. IPs without any are removed.
+    open: Mutex<HashMap<IpAddr, usize>>,
+}
+impl IpLimiter {
+    /// Constructor for the IpLimiter.
+    ///
+    /// # Arguments
+    /// - `max`: The maximum number of connections that may be open concurrently per IP.
+    ///
+    /// # Returns
+    /// A new IpLimiter without any open connections.
+    #[inline]
+    pub fn new(max: usize) -> Self { Self { max, open: Mutex::new(HashMap::new()) } }
+
+    /// Attempts to open a new connection for the given IP.
+    ///
+    /// # Arguments
+    /// - `ip`: The IP of the client that opens the connection.
+    ///
+    /// # Returns
+    /// An [`IpPermit`] that keeps the connection counted until it is dropped, or [`None`] if the IP already has the maximum number of
+    /// connections open.
+    pub fn try_acquire(self: &Arc<Self>, ip: IpAddr) -> Option<IpPermit> {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        let mut open = self.open.lock().unwrap();
+        let count: &mut usize = open.entry(ip).or_insert(0);
+        if *count >= self.max {
+            return None;
+        }
+        *count += 1;
+        Some(IpPermit { limiter: self.clone(), ip })
+    }
+}
+
+/// Counts a connection towards its IP's limit in an [`IpLimiter`] until it is dropped.
+#[derive(Debug)]
+pub struct IpPermit {
+    /// The limiter to which the connection counts.
+    limiter: Arc<IpLimiter>,
+    /// The IP of the client that opened the connection.
+    ip: IpAddr,
+}
+impl Drop for IpPermit {
+    fn drop(&mut self) {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        let mut open = self.limiter.open.lock().unwrap();
+        if let Some(count) = open.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                open.remove(&self.ip);
+            }
+        }
+    }
+}
+
+
+
+/// Tracks the number of bytes sent per client IP, in windows of 24 hours that start with the first request of the IP.
+#[derive(Debug)]
+pub struct QuotaTracker {
+    /// The maximum number of bytes per IP per window.
+    max:   u64,
+    /// The windows per IP, and the time at which expired windows were last pruned.
+    state: Mutex<(HashMap<IpAddr, QuotaWindow>, Instant)>,
+}
+impl QuotaTracker {
+    /// The length of a window.
+    pub const WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+    /// How often expired windows are pruned.
+    const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+    /// Constructor for the QuotaTracker.
+    ///
+    /// # Arguments
+    /// - `max`: The maximum number of bytes that may be sent to a single IP per window.
+    ///
+    /// # Returns
+    /// A new QuotaTracker without any windows.
+    #[inline]
+    pub fn new(max: u64) -> Self { Self { max, state: Mutex::new((HashMap::new(), Instant::now())) } }
+
+    /// Checks whether the given IP may be sent anything.
+    ///
+    /// # Arguments
+    /// - `ip`: The IP of the client.
+    /// - `now`: The current time.
+    ///
+    /// # Returns
+    /// [`None`] if the IP is still within its quota, or the time until its window resets otherwise.
+    pub fn check(&self, ip: IpAddr, now: Instant) -> Option<Duration> {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        let mut state = self.state.lock().unwrap();
+        let (windows, pruned): &mut (HashMap<IpAddr, QuotaWindow>, Instant) = &mut state;
+        if now.saturating_duration_since(*pruned) >= Self::PRUNE_INTERVAL {
+            windows.retain(|_, window| now.saturating_duration_since(window.start) < Self::WINDOW);
+            *pruned = now;
+        }
+        match windows.get(&ip) {
+            Some(window) if window.sent >= self.max => Self::WINDOW.checked_sub(now.saturating_duration_since(window.start)),
+            _ => None,
+        }
+    }
+
+    /// Counts bytes sent to the given IP towards its quota.
+    ///
+    /// # Arguments
+    /// - `ip`: The IP of the client.
+    /// - `bytes`: The number of bytes sent.
+    /// - `now`: The current time.
+    pub fn record(&self, ip: IpAddr, bytes: u64, now: Instant) {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        let mut state = self.state.lock().unwrap();
+        let window: &mut QuotaWindow = state.0.entry(ip).or_insert(QuotaWindow { start: now, sent: 0 });
+        if now.saturating_duration_since(window.start) >= Self::WINDOW {
+            *window = QuotaWindow { start: now, sent: 0 };
+        }
+        window.sent = window.sent.saturating_add(bytes);
+    }
+}
+
+/// The bytes sent to a single IP in its current window of a [`QuotaTracker`].
+#[derive(Clone, Copy, Debug)]
+struct QuotaWindow {
+    /// The time at which the window started.
+    start: Instant,
+    /// The number of bytes sent since then.
+    sent:  u64,
+}
+
+
+
+/// Refuses new requests for a while once too many requests in flight are taking too long, as configured by [`OverloadProtection`].
+#[derive(Debug)]
+pub struct OverloadBreaker {
+    /// When requests count as slow, how many may be, and for how long new ones are refused after.
+    config: OverloadProtection,
+    /// The requests in flight (by ID) and the time they started at, the next ID, and until when new requests are refused (if at all).
+    state:  Mutex<(HashMap<u64, Instant>, u64, Option<Instant>)>,
+}
+impl OverloadBreaker {
+    /// Constructor for the OverloadBreaker.
+    ///
+    /// # Arguments
+    /// - `config`: The [`OverloadProtection`] that defines when it triggers.
+    ///
+    /// # Returns
+    /// A new OverloadBreaker without any requests in flight.
+    #[inline]
+    pub fn new(config: OverloadProtection) -> Self { Self { config, state: Mutex::new((HashMap::new(), 0, None)) } }
+
+    /// Attempts to start handling a new request.
+    ///
+    /// # Arguments
+    /// - `now`: The current time.
+    ///
+    /// # Returns
+    /// An [`InFlight`] that keeps the request tracked until it is dropped, or [`None`] if the request should be refused because the server
+    /// is (or just became) overloaded.
+    pub fn enter(self: &Arc<Self>, now: Instant) -> Option<InFlight> {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        let mut state = self.state.lock().unwrap();
+        let (in_flight, next_id, until): &mut (HashMap<u64, Instant>, u64, Option<Instant>) = &mut state;
+        if until.is_some_and(|until| now < until) {
+            return None;
+        }
+        *until = None;
+
+        // See if too many requests are taking too long
+        let threshold: Duration = Duration::from_millis(self.config.latency_ms);
+        let slow: usize = in_flight.values().filter(|start| now.saturating_duration_since(**start) >= threshold).count();
+        if slow >= self.config.trigger_count {
+            warn!(
+                target: RATELIMIT,
+                "{slow} request(s) have been in flight for over {}ms; refusing new requests for {}s",
+                self.config.latency_ms,
+                self.config.cooldown_seconds
+            );
+            *until = Some(now + Duration::from_secs(self.config.cooldown_seconds));
+            return None;
+        }
+
+        let id: u64 = *next_id;
+        *next_id += 1;
+        in_flight.insert(id, now);
+        Some(InFlight { breaker: self.clone(), id })
+    }
+}
+
+/// Tracks a request in an [`OverloadBreaker`] until it is dropped.
+#[derive(Debug)]
+pub struct InFlight {
+    /// The breaker that tracks the request.
+    breaker: Arc<OverloadBreaker>,
+    /// The ID of the request in it.
+    id:      u64,
+}
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        self.breaker.state.lock().unwrap().0.remove(&self.id);
+    }
+}
+
+
+
 /// Defines the context in which paths are executed.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Context {
@@ -107,11 +1095,296 @@ pub struct Context {
     /// Some version for the file.
     #[serde(skip)]
     pub version: &'static str,
+    /// The value of the `Server`-header, computed from the name and version.
+    #[serde(skip)]
+    pub server_header: Option<HeaderValue>,
 
+    /// The address on which the server binds itself. Can be overridden on the command-line.
+    #[serde(default)]
+    pub address: Option<SocketAddr>,
     /// The path to where the site files are located.
     pub site: PathBuf,
-    /// The file sent back when a file isn't found.
-    pub not_found_file: PathBuf,
+    /// The file sent back when a file isn't found. May also be given per MIME type.
+    pub not_found_file: NotFoundFile,
+    /// Overrides the not found file for requests under specific path patterns. The files are relative to the site directory, and the
+    /// most specific matching pattern wins (see [`PathMatcher`]).
+    #[serde(default)]
+    pub not_found_overrides: HashMap<String, PathBuf>,
+    /// The compiled version of `not_found_overrides`.
+    #[serde(skip)]
+    pub not_found_matcher: PathMatcher<PathBuf>,
+    /// The path patterns of content that was removed for good, which are answered with 410 GONE instead of being resolved (see
+    /// [`PathMatcher`]).
+    #[serde(default)]
+    pub gone_paths: Vec<String>,
+    /// The compiled version of `gone_paths`.
+    #[serde(skip)]
+    pub gone_matcher: PathMatcher<()>,
+    /// If given, the page (relative to the site directory) that is sent for `gone_paths`. Resolved to its canonical path when loading the
+    /// config.
+    #[serde(default)]
+    pub gone_file: Option<PathBuf>,
+    /// Case-insensitive substrings of the `User-Agent`s of crawlers, which are sent the `bot_files` instead of what they asked for.
+    #[serde(default)]
+    pub bot_user_agents: Vec<String>,
+    /// Maps path patterns to the files (relative to the site directory) that are sent to crawlers instead (see [`PathMatcher`]).
+    #[serde(default)]
+    pub bot_files: HashMap<String, PathBuf>,
+    /// The compiled version of `bot_files`.
+    #[serde(skip)]
+    pub bot_matcher: PathMatcher<PathBuf>,
+    /// Whether to create the site directory and a default not found file if they don't exist. If false, they must exist.
+    #[serde(default = "default_true")]
+    pub create_missing: bool,
+    /// Files that may never be served directly, resolved relative to the site directory. The not found file and the config file are
+    /// automatically protected if they reside within the site directory.
+    #[serde(default)]
+    pub protected_files: Vec<PathBuf>,
+    /// The status code sent for protected files. Either 404 (the default, which doesn't reveal that they exist) or 403.
+    #[serde(default = "default_not_found_status")]
+    pub blocked_status: u16,
+    /// The canonical paths of all protected files, computed when loading the config.
+    #[serde(skip)]
+    pub protected: HashSet<PathBuf>,
+    /// The status code with which the not found file is sent back.
+    #[serde(default = "default_not_found_status")]
+    pub not_found_status: u16,
+    /// Additional headers that are sent along with the not found file only.
+    #[serde(default)]
+    pub not_found_headers: HashMap<String, String>,
+    /// The parsed version of `not_found_headers`, computed when loading the config.
+    #[serde(skip)]
+    pub not_found_header_map: HeaderMap,
+    /// The maximum number of headers in a response. Responses with more are replaced by a 500 INTERNAL SERVER ERROR.
+    #[serde(default = "default_max_response_headers")]
+    pub max_response_headers: usize,
+    /// The maximum total size (in bytes) of the headers in a response. Responses with larger ones are replaced by a 500 INTERNAL SERVER
+    /// ERROR.
+    #[serde(default = "default_max_response_header_bytes")]
+    pub max_response_header_bytes: usize,
+    /// The names of headers that are removed from every response, after everything else has set its headers.
+    #[serde(default)]
+    pub strip_response_headers: Vec<String>,
+    /// The parsed version of `strip_response_headers`.
+    #[serde(skip)]
+    pub stripped_headers: Vec<HeaderName>,
+    /// Whether to refuse requests with absolute-form targets (e.g., `GET http://example.com/`) instead of serving their path.
+    #[serde(default)]
+    pub reject_absolute_form: bool,
+    /// Methods (besides GET and HEAD) that are treated as if they are GET for site files.
+    #[serde(default)]
+    pub get_alias_methods: Vec<String>,
+    /// The status code (200 or 204) with which `OPTIONS`-requests are answered, both server-wide (`OPTIONS *`) and for paths.
+    #[serde(default = "default_options_status")]
+    pub options_status: u16,
+    /// The status code (405 or 403) with which `TRACE`-requests are refused. They are never echoed back.
+    #[serde(default = "default_trace_status")]
+    pub trace_status: u16,
+    /// If given, a YAML or CSV file with redirects that are checked before resolving any files. Reloaded on `SIGHUP`.
+    #[serde(default)]
+    pub redirects: Option<PathBuf>,
+    /// The redirects loaded from `redirects`.
+    #[serde(skip)]
+    pub redirect_map: Arc<RwLock<Redirects>>,
+    /// The path on which the server reports its name and version as JSON. Disabled if empty.
+    #[serde(default)]
+    pub version_path: String,
+    /// If given, serves a read-only admin API (authenticated with a bearer token) that reports on the running server.
+    #[serde(default)]
+    pub admin: Option<AdminApi>,
+    /// If given, serves ACME HTTP-01 challenges from a separate directory, bypassing the site.
+    #[serde(default)]
+    pub acme_challenge: Option<AcmeChallenge>,
+    /// If given, serves a manifest with the SRI hashes of the site's assets.
+    #[serde(default)]
+    pub sri_manifest: Option<SriManifest>,
+    /// If given, serves Server-Sent Events that tell browsers when the site changed (for development only).
+    #[serde(default)]
+    pub livereload: Option<LiveReload>,
+    /// If given, paths that may only be accessed through signed URLs with an expiry time.
+    #[serde(default)]
+    pub signed_urls: Option<SignedUrls>,
+    /// The number of seconds after which clients are told to retry when the server is temporarily unavailable.
+    #[serde(default = "default_retry_after_seconds")]
+    pub retry_after_seconds: u64,
+    /// The number of times transient filesystem errors (`EIO`, `ESTALE`) are retried before giving up. Useful on network filesystems.
+    #[serde(default)]
+    pub fs_retries: u32,
+    /// The number of seconds to wait after binding before accepting connections, e.g., to let a load balancer drain old instances first.
+    #[serde(default)]
+    pub startup_delay_seconds: u64,
+    /// If given, connections are only accepted once this file exists.
+    #[serde(default)]
+    pub readiness_file: Option<PathBuf>,
+    /// The maximum number of files that may be read concurrently. Further reads wait until they may go. Unlimited if omitted.
+    #[serde(default)]
+    pub max_concurrent_reads: Option<usize>,
+    /// Limits the concurrent reads according to `max_concurrent_reads`.
+    #[serde(skip)]
+    pub read_limiter: Option<Arc<ReadLimiter>>,
+    /// The maximum number of connections that may be open concurrently from a single client IP. Further connections are closed
+    /// immediately. Unlimited if omitted.
+    #[serde(default)]
+    pub max_connections_per_ip: Option<usize>,
+    /// Limits the connections per IP according to `max_connections_per_ip`.
+    #[serde(skip)]
+    pub ip_limiter: Option<Arc<IpLimiter>>,
+    /// Maps path patterns to the maximum number of bytes per second at which matching files are sent (see [`PathMatcher`]). Unlimited
+    /// for paths that match none.
+    #[serde(default)]
+    pub bandwidth_limits: HashMap<String, u64>,
+    /// The compiled version of `bandwidth_limits`.
+    #[serde(skip)]
+    pub bandwidth_matcher: PathMatcher<u64>,
+    /// The maximum number of bytes that are sent to a single client IP per 24 hours. Further requests get a 429 TOO MANY REQUESTS until
+    /// its window resets. Unlimited if omitted.
+    #[serde(default)]
+    pub daily_quota_bytes: Option<u64>,
+    /// Tracks the bytes sent per IP according to `daily_quota_bytes`.
+    #[serde(skip)]
+    pub quota_tracker: Option<Arc<QuotaTracker>>,
+    /// If given, new requests get a 503 SERVICE UNAVAILABLE for a while once too many requests are taking too long. Disabled if omitted.
+    #[serde(default)]
+    pub overload_protection: Option<OverloadProtection>,
+    /// Tracks the requests in flight according to `overload_protection`.
+    #[serde(skip)]
+    pub overload_breaker: Option<Arc<OverloadBreaker>>,
+    /// If given, delays responses on purpose (for testing only).
+    #[serde(default)]
+    pub artificial_delay: Option<ArtificialDelay>,
+    /// Whether to log a warning whenever an empty file is served.
+    #[serde(default)]
+    pub warn_on_empty: bool,
+    /// Whether to send the not found page instead of empty HTML files.
+    #[serde(default)]
+    pub empty_html_not_found: bool,
+    /// Whether to send `X-Served-File` and `X-Resolution` headers describing how a request was resolved. Leaks the site's structure, so
+    /// only meant for development.
+    #[serde(default)]
+    pub debug_headers: bool,
+    /// If given, logs a line for every request (on the `swh::access` target).
+    #[serde(default)]
+    pub access_log: Option<AccessLog>,
+    /// Statistics about the running server, which can be dumped to the log by sending it `SIGUSR1`.
+    #[serde(skip)]
+    pub stats: Arc<Stats>,
+    /// Whether to honour `Range`-requests (including multiple ranges at once).
+    #[serde(default = "default_true")]
+    pub ranges: bool,
+    /// Files of at most this many bytes are read into memory at once and sent as a single body, rather than being streamed in chunks.
+    /// Disabled if `0`.
+    #[serde(default)]
+    pub small_file_threshold: u64,
+    /// The level at which files that aren't found are logged.
+    #[serde(default)]
+    pub not_found_log_level: NotFoundLogLevel,
+    /// The maximum length of request paths, in bytes. Longer paths are refused with 414 URI TOO LONG.
+    #[serde(default = "default_max_path_length")]
+    pub max_path_length: usize,
+    /// The maximum number of components of request paths. Deeper paths are refused with 400 BAD REQUEST.
+    #[serde(default = "default_max_path_components")]
+    pub max_path_components: usize,
+    /// If given, the file (relative to the site directory) that is served for every request, regardless of its path. Resolved to its
+    /// canonical path when loading the config.
+    #[serde(default)]
+    pub single_file: Option<PathBuf>,
+    /// Whether request paths must match the casing of the files on disk exactly, even on case-insensitive filesystems.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// How to deal with `..` in request paths.
+    #[serde(default)]
+    pub parent_dirs: ParentDirPolicy,
+    /// If given, the file (relative to the site directory) that is served for `/` instead of its index file. Resolved to its canonical path
+    /// (if it exists) when loading the config.
+    #[serde(default)]
+    pub root_file: Option<PathBuf>,
+    /// The ways in which request paths are resolved to files, tried in order.
+    #[serde(default = "default_resolution_order")]
+    pub resolution_order: Vec<Resolution>,
+    /// The files that are tried, in order, when a directory is requested.
+    #[serde(default = "default_index_files")]
+    pub index_files: Vec<String>,
+    /// Whether to log a warning when a directory has more than one of its index files.
+    #[serde(default)]
+    pub warn_on_ambiguous_index: bool,
+    /// Whether to log a warning when a request path resolves to both `<path>.html` and an index file of `<path>/`.
+    #[serde(default)]
+    pub warn_on_clean_url_collision: bool,
+    /// Whether explicit requests for index files (e.g., `/foo/index.html`) are redirected to their directory (e.g., `/foo/`).
+    #[serde(default)]
+    pub index_redirect: bool,
+    /// Overrides `index_files` for directories under specific path patterns. The most specific matching pattern wins (see
+    /// [`PathMatcher`]).
+    #[serde(default)]
+    pub index_overrides: HashMap<String, Vec<String>>,
+    /// The compiled version of `index_overrides`.
+    #[serde(skip)]
+    pub index_matcher: PathMatcher<Vec<String>>,
+    /// If given, picks `index.<lang>.html` variants based on the client's `Accept-Language`.
+    #[serde(default)]
+    pub language_negotiation: Option<LanguageNegotiation>,
+    /// Maps file extensions (without leading dot) to MIME types, overriding or extending the built-in ones.
+    #[serde(default)]
+    pub mime_types: HashMap<String, String>,
+    /// Whether to sniff the MIME type of files with an unknown (or without an) extension from the signature in their first bytes, falling
+    /// back to the default MIME type if there is none.
+    #[serde(default)]
+    pub sniff_content: bool,
+    /// The charset that is appended to the content type of textual files.
+    #[serde(default = "default_charset")]
+    pub charset: String,
+    /// Overrides the `charset` for files with specific extensions (without leading dot).
+    #[serde(default)]
+    pub charset_overrides: HashMap<String, String>,
+    /// Whether to sniff the charset of textual files (without a `charset_overrides`) from a byte order mark or a declaration in their
+    /// first bytes, falling back to `charset` if there is none.
+    #[serde(default)]
+    pub sniff_charset: bool,
+    /// If given, the line endings to which those of textual files are normalized while serving them. Disabled if omitted.
+    #[serde(default)]
+    pub normalize_line_endings: Option<LineEnding>,
+    /// Whether to strip the UTF-8 byte order mark from the start of textual files while serving them.
+    #[serde(default)]
+    pub strip_bom: bool,
+    /// The maximum size, in bytes, of files that are transformed (e.g., by `normalize_line_endings`), as that reads them into memory.
+    /// Larger files are sent as-is.
+    #[serde(default = "default_transform_max_size")]
+    pub transform_max_size: u64,
+    /// If given, HTML files are sent with this `Content-Security-Policy`, with a fresh nonce injected in both. Disabled if omitted.
+    #[serde(default)]
+    pub csp_nonce: Option<CspNonce>,
+    /// Maps path patterns of HTML files (relative to the site directory) to assets they tell browsers to fetch early (see
+    /// [`PathMatcher`]).
+    #[serde(default)]
+    pub preload: HashMap<String, Vec<Preload>>,
+    /// The compiled version of `preload`, as `Link`-headers.
+    #[serde(skip)]
+    pub preload_matcher: PathMatcher<HeaderValue>,
+    /// A regular expression matching the filenames of fingerprinted assets (e.g., `app.4f3a9b.js`), which are sent as immutable.
+    #[serde(default)]
+    pub fingerprint_pattern: Option<String>,
+    /// The compiled version of the `fingerprint_pattern`.
+    #[serde(skip)]
+    pub fingerprint: Option<Regex>,
+    /// What the `ETag`s of files are computed from.
+    #[serde(default)]
+    pub etag: EtagStrategy,
+    /// The content hashes computed so far if `etag` is [`EtagStrategy::ContentHash`], which are reused while the files don't change.
+    #[serde(skip)]
+    pub etag_cache: Arc<SriCache>,
+    /// Whether to read the client address from PROXY protocol (v1 or v2) headers sent by a load balancer.
+    #[serde(default)]
+    pub proxy_protocol: ProxyProtocol,
+    /// The number of milliseconds that clients get to send their PROXY protocol header, after which their connection is dropped.
+    #[serde(default = "default_proxy_header_timeout_ms")]
+    pub proxy_header_timeout_ms: u64,
+    /// The HTTP versions that the server speaks. Connections using other versions are closed.
+    #[serde(default = "default_http_versions")]
+    pub http_versions: Vec<HttpVersion>,
+    /// Whether HTTP/1 connections are kept alive after a response. If not, every response carries `Connection: close` and its connection
+    /// is closed once it is sent.
+    #[serde(default = "default_true")]
+    pub keep_alive: bool,
 }
 impl Context {
     /// Constructor for the Context that loads it from a given file.
@@ -125,44 +1398,333 @@ impl Context {
     /// A new Context loaded from the given `path`.
     ///
     /// # Errors
-    /// This function can fail if we failed to open, read or parse the given file as YAML.
+    /// This function can fail if we failed to open, read or parse the given file as YAML; if it's not valid; or if the site directory or not
+    /// found file are missing and could (or may) not be created.
     #[inline]
     pub fn new(name: &'static str, version: &'static str, path: impl AsRef<Path>) -> Result<Self, Error> {
         let path: &Path = path.as_ref();
 
         // Open the file
-        debug!("Reading config file at '{}'...", path.display());
-        let handle: File = match File::open(path) {
-            Ok(handle) => handle,
-            Err(err) => {
-                if err.kind() == ErrorKind::NotFound {
-                    // Generate a default one instead
-                    info!("No config file found at '{}'; generating default...", path.display());
-                    let def: Self = Self { name, version, site: "./www".into(), not_found_file: "./www/not_found.html".into() };
-                    match File::create(path) {
-                        Ok(handle) => {
-                            if let Err(err) = serde_yml::to_writer(handle, &def) {
-                                return Err(Error::ConfigWrite { path: path.into(), err });
-                            }
-                        },
-                        Err(err) => return Err(Error::ConfigCreate { path: path.into(), err }),
-                    }
-                    return Ok(def);
-                } else {
-                    return Err(Error::ConfigOpen { path: path.into(), err });
+        debug!(target: CONFIG, "Reading config file at '{}'...", path.display());
+        let mut config: Self = match File::open(path) {
+            // Read it with serde
+            Ok(handle) => match serde_yml::from_reader(handle) {
+                Ok(config) => config,
+                Err(err) => return Err(Error::ConfigParse { path: path.into(), err }),
+            },
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                // Generate a default one instead
+                info!(target: CONFIG, "No config file found at '{}'; generating default...", path.display());
+                let def: Self = Self {
+                    name,
+                    version,
+                    server_header: None,
+                    address: None,
+                    site: "./www".into(),
+                    not_found_file: NotFoundFile::Single("./www/not_found.html".into()),
+                    not_found_overrides: HashMap::new(),
+                    not_found_matcher: PathMatcher::default(),
+                    gone_paths: Vec::new(),
+                    gone_matcher: PathMatcher::default(),
+                    gone_file: None,
+                    bot_user_agents: Vec::new(),
+                    bot_files: HashMap::new(),
+                    bot_matcher: PathMatcher::default(),
+                    create_missing: true,
+                    protected_files: Vec::new(),
+                    blocked_status: default_not_found_status(),
+                    protected: HashSet::new(),
+                    not_found_status: default_not_found_status(),
+                    not_found_headers: HashMap::new(),
+                    not_found_header_map: HeaderMap::new(),
+                    max_response_headers: default_max_response_headers(),
+                    max_response_header_bytes: default_max_response_header_bytes(),
+                    strip_response_headers: Vec::new(),
+                    stripped_headers: Vec::new(),
+                    reject_absolute_form: false,
+                    get_alias_methods: Vec::new(),
+                    options_status: default_options_status(),
+                    trace_status: default_trace_status(),
+                    redirects: None,
+                    redirect_map: Arc::default(),
+                    version_path: String::new(),
+                    admin: None,
+                    acme_challenge: None,
+                    sri_manifest: None,
+                    livereload: None,
+                    signed_urls: None,
+                    retry_after_seconds: default_retry_after_seconds(),
+                    fs_retries: 0,
+                    startup_delay_seconds: 0,
+                    readiness_file: None,
+                    max_concurrent_reads: None,
+                    read_limiter: None,
+                    max_connections_per_ip: None,
+                    ip_limiter: None,
+                    bandwidth_limits: HashMap::new(),
+                    bandwidth_matcher: PathMatcher::default(),
+                    daily_quota_bytes: None,
+                    quota_tracker: None,
+                    overload_protection: None,
+                    overload_breaker: None,
+                    artificial_delay: None,
+                    warn_on_empty: false,
+                    empty_html_not_found: false,
+                    debug_headers: false,
+                    access_log: None,
+                    stats: Arc::default(),
+                    ranges: true,
+                    small_file_threshold: 0,
+                    not_found_log_level: NotFoundLogLevel::Debug,
+                    max_path_length: default_max_path_length(),
+                    max_path_components: default_max_path_components(),
+                    single_file: None,
+                    case_sensitive: false,
+                    parent_dirs: ParentDirPolicy::Resolve,
+                    root_file: None,
+                    resolution_order: default_resolution_order(),
+                    index_files: default_index_files(),
+                    warn_on_ambiguous_index: false,
+                    warn_on_clean_url_collision: false,
+                    index_redirect: false,
+                    index_overrides: HashMap::new(),
+                    index_matcher: PathMatcher::default(),
+                    language_negotiation: None,
+                    mime_types: HashMap::new(),
+                    sniff_content: false,
+                    charset: default_charset(),
+                    charset_overrides: HashMap::new(),
+                    sniff_charset: false,
+                    normalize_line_endings: None,
+                    strip_bom: false,
+                    transform_max_size: default_transform_max_size(),
+                    csp_nonce: None,
+                    preload: HashMap::new(),
+                    preload_matcher: PathMatcher::default(),
+                    fingerprint_pattern: None,
+                    fingerprint: None,
+                    etag: EtagStrategy::MtimeSize,
+                    etag_cache: Arc::default(),
+                    proxy_protocol: ProxyProtocol::Off,
+                    proxy_header_timeout_ms: default_proxy_header_timeout_ms(),
+                    http_versions: default_http_versions(),
+                    keep_alive: true,
+                };
+                match File::create(path) {
+                    Ok(handle) => {
+                        if let Err(err) = serde_yml::to_writer(handle, &def) {
+                            return Err(Error::ConfigWrite { path: path.into(), err });
+                        }
+                    },
+                    Err(err) => return Err(Error::ConfigCreate { path: path.into(), err }),
                 }
+                // NOTE: Continue with it as if we read it, so the site directory and not found file get the same treatment
+                def
             },
+            Err(err) => return Err(Error::ConfigOpen { path: path.into(), err }),
         };
 
-        // Read it with serde
-        let mut config: Self = match serde_yml::from_reader(handle) {
-            Ok(config) => config,
-            Err(err) => return Err(Error::ConfigParse { path: path.into(), err }),
-        };
+        // Validate it
+        if config.http_versions.is_empty() {
+            return Err(Error::IllegalHttpVersions);
+        }
+        if config.resolution_order.is_empty() {
+            return Err(Error::IllegalResolutionOrder);
+        }
+        if let Some(root_file) = &config.root_file {
+            if !root_file.components().all(|comp| matches!(comp, Component::Normal(_) | Component::CurDir)) || root_file.as_os_str().is_empty() {
+                return Err(Error::IllegalRootFile { path: root_file.clone() });
+            }
+        }
+        for name in config.index_files.iter().chain(config.index_overrides.values().flatten()) {
+            if !is_valid_index_file(name) {
+                return Err(Error::IllegalIndexFile { name: name.clone() });
+            }
+        }
+        for (ext, mime) in &config.mime_types {
+            if !mime.contains('/') || HeaderValue::from_str(mime).is_err() {
+                return Err(Error::IllegalMimeType { ext: ext.clone(), mime: mime.clone() });
+            }
+        }
+        for charset in std::iter::once(&config.charset).chain(config.charset_overrides.values()) {
+            if !is_valid_charset(charset) {
+                return Err(Error::IllegalCharset { charset: charset.clone() });
+            }
+        }
+        if StatusCode::from_u16(config.not_found_status).is_err() {
+            return Err(Error::IllegalNotFoundStatus { code: config.not_found_status });
+        }
+        let mut not_found_header_map: HeaderMap = HeaderMap::new();
+        for (name, value) in &config.not_found_headers {
+            let header_name: HeaderName = match HeaderName::from_bytes(name.as_bytes()) {
+                Ok(header_name) => header_name,
+                Err(err) => return Err(Error::IllegalNotFoundHeaderName { name: name.clone(), err }),
+            };
+            let header_value: HeaderValue = match HeaderValue::from_str(value) {
+                Ok(header_value) => header_value,
+                Err(err) => return Err(Error::IllegalNotFoundHeaderValue { name: name.clone(), err }),
+            };
+            not_found_header_map.insert(header_name, header_value);
+        }
+        let bytes: usize = header_bytes(&not_found_header_map);
+        if not_found_header_map.len() > config.max_response_headers || bytes > config.max_response_header_bytes {
+            return Err(Error::IllegalNotFoundHeaders { count: not_found_header_map.len(), bytes });
+        }
+        config.not_found_header_map = not_found_header_map;
+        for name in &config.strip_response_headers {
+            let header_name: HeaderName = match HeaderName::from_bytes(name.as_bytes()) {
+                Ok(header_name) => header_name,
+                Err(err) => return Err(Error::IllegalStrippedHeaderName { name: name.clone(), err }),
+            };
+            // NOTE: hyper writes these itself (if missing) while sending the response, so stripping them would silently do nothing
+            if [header::CONNECTION, header::CONTENT_LENGTH, header::DATE, header::TRANSFER_ENCODING].contains(&header_name) {
+                return Err(Error::UnstrippableHeader { name: name.clone() });
+            }
+            config.stripped_headers.push(header_name);
+        }
+        for method in &config.get_alias_methods {
+            match Method::from_bytes(method.as_bytes()) {
+                Ok(Method::GET | Method::HEAD) => warn!(target: CONFIG, "Aliasing {method} as GET is redundant"),
+                Ok(Method::POST | Method::PUT | Method::DELETE | Method::PATCH) => {
+                    warn!(
+                        target: CONFIG,
+                        "Aliasing {method} as GET; clients will be told that state-changing requests succeeded without anything happening"
+                    )
+                },
+                // Echoing requests enables cross-site tracing, so TRACE is always refused instead; and OPTIONS is always answered itself
+                Ok(Method::TRACE | Method::OPTIONS) | Err(_) => return Err(Error::IllegalGetAliasMethod { method: method.clone() }),
+                Ok(_) => {},
+            }
+        }
+        if !matches!(config.blocked_status, 403 | 404) {
+            return Err(Error::IllegalBlockedStatus { code: config.blocked_status });
+        }
+        if !matches!(config.trace_status, 403 | 405) {
+            return Err(Error::IllegalTraceStatus { code: config.trace_status });
+        }
+        if !matches!(config.options_status, 200 | 204) {
+            return Err(Error::IllegalOptionsStatus { code: config.options_status });
+        }
+        if !config.version_path.is_empty() && !is_valid_route(&config.version_path) {
+            return Err(Error::IllegalVersionPath { path: config.version_path });
+        }
+        if let Some(admin) = &mut config.admin {
+            let path: &str = admin.path.trim_end_matches('/');
+            if !is_valid_route(path) {
+                return Err(Error::IllegalAdminPath { path: admin.path.clone() });
+            }
+            admin.path = path.into();
+            if admin.token.is_empty() {
+                return Err(Error::EmptyAdminToken);
+            }
+        }
+        if config.signed_urls.as_ref().is_some_and(|signed| signed.secret.is_empty()) {
+            return Err(Error::EmptySignedUrlSecret);
+        }
+        if let Some(csp) = &config.csp_nonce {
+            if csp.placeholder.is_empty() {
+                return Err(Error::EmptyCspNoncePlaceholder);
+            }
+            // NOTE: Nonces are base64, so any will do to check whether the policy makes for a valid header
+            if HeaderValue::from_str(&csp.policy.replace(&csp.placeholder, "AAAAAAAAAAAAAAAAAAAAAA==")).is_err() {
+                return Err(Error::IllegalCspPolicy { policy: csp.policy.clone() });
+            }
+        }
+        if let Some(acme) = &mut config.acme_challenge {
+            let path: &str = acme.path.trim_end_matches('/');
+            if !is_valid_route(path) {
+                return Err(Error::IllegalAcmeChallengePath { path: acme.path.clone() });
+            }
+            acme.path = path.into();
+        }
+        if let Some(sri) = &config.sri_manifest {
+            if !is_valid_route(&sri.path) {
+                return Err(Error::IllegalSriManifestPath { path: sri.path.clone() });
+            }
+        }
+        if let Some(livereload) = &config.livereload {
+            if !is_valid_route(&livereload.path) {
+                return Err(Error::IllegalLiveReloadPath { path: livereload.path.clone() });
+            }
+            if livereload.poll_interval_ms == 0 {
+                return Err(Error::IllegalLiveReloadInterval);
+            }
+        }
+        if config.proxy_header_timeout_ms == 0 {
+            return Err(Error::IllegalProxyHeaderTimeout);
+        }
+        match config.max_concurrent_reads {
+            Some(0) => return Err(Error::IllegalMaxConcurrentReads),
+            Some(max) => config.read_limiter = Some(Arc::new(ReadLimiter::new(max))),
+            None => {},
+        }
+        config.stats = Arc::new(Stats::new(config.read_limiter.clone()));
+        match config.max_connections_per_ip {
+            Some(0) => return Err(Error::IllegalMaxConnectionsPerIp),
+            Some(max) => config.ip_limiter = Some(Arc::new(IpLimiter::new(max))),
+            None => {},
+        }
+        if let Some((pattern, _)) = config.bandwidth_limits.iter().find(|(_, rate)| **rate == 0) {
+            return Err(Error::IllegalBandwidthLimit { pattern: pattern.clone() });
+        }
+        match config.daily_quota_bytes {
+            Some(0) => return Err(Error::IllegalDailyQuota),
+            Some(max) => config.quota_tracker = Some(Arc::new(QuotaTracker::new(max))),
+            None => {},
+        }
+        if let Some(overload) = config.overload_protection {
+            for (field, value) in [
+                ("latency_ms", overload.latency_ms),
+                ("trigger_count", overload.trigger_count as u64),
+                ("cooldown_seconds", overload.cooldown_seconds),
+            ] {
+                if value == 0 {
+                    return Err(Error::IllegalOverloadProtection { field });
+                }
+            }
+            config.overload_breaker = Some(Arc::new(OverloadBreaker::new(overload)));
+        }
+        if let Some(delay) = &mut config.artificial_delay {
+            let max_ms: u64 = delay.max_ms.unwrap_or(delay.min_ms);
+            if max_ms < delay.min_ms {
+                return Err(Error::IllegalArtificialDelay { min_ms: delay.min_ms, max_ms });
+            }
+            delay.matcher = PathMatcher::new(delay.paths.iter().map(|pattern| (pattern.as_str(), ())));
+            warn!(target: CONFIG, "Delaying responses by {}-{max_ms}ms on purpose (for testing only)", delay.min_ms);
+        }
+        if let Some(access) = &mut config.access_log {
+            for name in &access.log_headers {
+                match HeaderName::from_bytes(name.as_bytes()) {
+                    Ok(header_name) => access.headers.push(header_name),
+                    Err(err) => return Err(Error::IllegalLogHeader { name: name.clone(), err }),
+                }
+            }
+            if access.hash_client_ip {
+                if let Err(err) = getrandom::getrandom(&mut access.ip_key) {
+                    return Err(Error::AccessLogKey { err });
+                }
+            }
+        }
+        if let Some(pattern) = &config.fingerprint_pattern {
+            match Regex::new(pattern) {
+                Ok(regex) => config.fingerprint = Some(regex),
+                Err(err) => return Err(Error::IllegalFingerprintPattern { pattern: pattern.clone(), err }),
+            }
+        }
+
+        if config.debug_headers {
+            warn!(
+                target: CONFIG,
+                "Debug headers are enabled; responses reveal the structure of the site directory (do not use this in production)"
+            );
+        }
 
         // Create the www directory if it doesn't exist
         if !config.site.exists() {
-            warn!("Site directory '{}' does not exist; creating it...", config.site.display());
+            if !config.create_missing {
+                return Err(Error::SiteDirMissing { path: config.site });
+            }
+            warn!(target: CONFIG, "Site directory '{}' does not exist; creating it...", config.site.display());
             if let Err(err) = fs::create_dir_all(&config.site) {
                 return Err(Error::SiteDirCreate { path: config.site, err });
             }
@@ -174,16 +1736,168 @@ impl Context {
         };
 
         // Create the not found file if it doesn't exist
-        if !config.not_found_file.exists() {
-            warn!("Not found file '{}' does not exist; creating it...", config.not_found_file.display());
-            if let Err(err) = fs::write(&config.not_found_file, DEFAULT_NOT_FOUND_FILE) {
-                return Err(Error::NotFoundFileCreate { path: config.not_found_file, err });
+        if let NotFoundFile::ByType(files) = &config.not_found_file {
+            if !files.contains_key("text/html") {
+                return Err(Error::MissingHtmlNotFoundFile);
+            }
+        }
+        for (mime, path) in config.not_found_file.files() {
+            if path.exists() {
+                continue;
+            }
+            if let Some(mime) = mime.filter(|mime| *mime != "text/html") {
+                return Err(Error::NotFoundVariantMissing { mime: mime.into(), path: path.into() });
+            }
+            if !config.create_missing {
+                return Err(Error::NotFoundFileMissing { path: path.into() });
+            }
+            warn!(target: CONFIG, "Not found file '{}' does not exist; creating it...", path.display());
+            if let Err(err) = fs::write(path, DEFAULT_NOT_FOUND_FILE) {
+                return Err(Error::NotFoundFileCreate { path: path.into(), err });
+            }
+        }
+        // NOTE: They all exist by now, so this only fails in case of races
+        for path in config.not_found_file.files_mut() {
+            if let Ok(canonical) = fs::canonicalize(&*path) {
+                *path = canonical;
+            }
+        }
+
+        // Resolve the root file within the site
+        // NOTE: It may not exist yet (e.g., before the first build), in which case `/` is simply not found until it does
+        if let Some(root_file) = &mut config.root_file {
+            let rel: PathBuf = root_file.components().filter(|comp| matches!(comp, Component::Normal(_))).collect();
+            let path: PathBuf = config.site.join(rel);
+            *root_file = fs::canonicalize(&path).unwrap_or(path);
+        }
+
+        // Resolve the not found files per prefix within the site
+        for (prefix, path) in &mut config.not_found_overrides {
+            match fs::canonicalize(config.site.join(&*path)) {
+                Ok(canonical) if canonical.starts_with(&config.site) && canonical.is_file() => *path = canonical,
+                Ok(_) => return Err(Error::NotFoundOverrideMissing { prefix: prefix.clone(), path: path.clone(), err: None }),
+                Err(err) => return Err(Error::NotFoundOverrideMissing { prefix: prefix.clone(), path: path.clone(), err: Some(err) }),
+            }
+        }
+
+        // Resolve the files for crawlers within the site
+        for (pattern, path) in &mut config.bot_files {
+            match fs::canonicalize(config.site.join(&*path)) {
+                Ok(canonical) if canonical.starts_with(&config.site) && canonical.is_file() => *path = canonical,
+                Ok(_) => return Err(Error::BotFileMissing { pattern: pattern.clone(), path: path.clone(), err: None }),
+                Err(err) => return Err(Error::BotFileMissing { pattern: pattern.clone(), path: path.clone(), err: Some(err) }),
+            }
+        }
+        // NOTE: Matching is case-insensitive, so do the work once
+        for agent in &mut config.bot_user_agents {
+            if agent.is_empty() {
+                return Err(Error::EmptyBotUserAgent);
+            }
+            *agent = agent.to_ascii_lowercase();
+        }
+
+        // Check the preloaded assets
+        for (pattern, entries) in &config.preload {
+            if let Some((entry, reason)) = entries.iter().find_map(|entry| entry.validate().map(|reason| (entry, reason))) {
+                return Err(Error::IllegalPreload { pattern: pattern.clone(), href: entry.href.clone(), reason });
             }
         }
 
+        // Compile the path rules
+        config.not_found_matcher =
+            PathMatcher::new(config.not_found_overrides.iter().map(|(pattern, path)| (pattern.as_str(), path.clone())));
+        config.index_matcher = PathMatcher::new(config.index_overrides.iter().map(|(pattern, names)| (pattern.as_str(), names.clone())));
+        config.bandwidth_matcher = PathMatcher::new(config.bandwidth_limits.iter().map(|(pattern, rate)| (pattern.as_str(), *rate)));
+        let links: Vec<(&str, HeaderValue)> = config
+            .preload
+            .iter()
+            .filter(|(_, entries)| !entries.is_empty())
+            .map(|(pattern, entries)| {
+                let link: String = entries.iter().map(Preload::link).collect::<Vec<String>>().join(", ");
+                // SAFETY: We can call `unwrap()` because the entries were validated to only consist of visible ASCII.
+                (pattern.as_str(), HeaderValue::from_str(&link).unwrap())
+            })
+            .collect();
+        config.preload_matcher = PathMatcher::new(links);
+        config.gone_matcher = PathMatcher::new(config.gone_paths.iter().map(|pattern| (pattern.as_str(), ())));
+        config.bot_matcher = PathMatcher::new(config.bot_files.iter().map(|(pattern, path)| (pattern.as_str(), path.clone())));
+        if let Some(signed) = &mut config.signed_urls {
+            signed.matcher = PathMatcher::new(signed.paths.iter().map(|pattern| (pattern.as_str(), ())));
+        }
+
+        // Load the redirects
+        if let Some(path) = &config.redirects {
+            let redirects: Redirects = match Redirects::load(path) {
+                Ok(redirects) => redirects,
+                Err(err) => return Err(Error::Redirects { err }),
+            };
+            info!(target: CONFIG, "Loaded {} redirect(s) from '{}'", redirects.len(), path.display());
+            config.redirect_map = Arc::new(RwLock::new(redirects));
+        }
+
+        // Resolve the file to serve for everything
+        if let Some(path) = &mut config.single_file {
+            match fs::canonicalize(config.site.join(&*path)) {
+                Ok(canonical) if canonical.starts_with(&config.site) && canonical.is_file() => *path = canonical,
+                Ok(_) => return Err(Error::SingleFileMissing { path: path.clone(), err: None }),
+                Err(err) => return Err(Error::SingleFileMissing { path: path.clone(), err: Some(err) }),
+            }
+            info!(target: CONFIG, "Serving '{}' for every request", path.display());
+        }
+
+        // Resolve the page for removed content
+        if let Some(path) = &mut config.gone_file {
+            match fs::canonicalize(config.site.join(&*path)) {
+                Ok(canonical) if canonical.starts_with(&config.site) && canonical.is_file() => *path = canonical,
+                Ok(_) => return Err(Error::GoneFileMissing { path: path.clone(), err: None }),
+                Err(err) => return Err(Error::GoneFileMissing { path: path.clone(), err: Some(err) }),
+            }
+        }
+
+        // Resolve the files that may not be served
+        let resolve = |path: &Path| -> PathBuf {
+            let path: PathBuf = config.site.join(path);
+            fs::canonicalize(&path).unwrap_or(path)
+        };
+        let mut protected: HashSet<PathBuf> = config.protected_files.iter().map(|path| resolve(path)).collect();
+        let not_found_files = config.not_found_file.files().map(|(_, path)| path).chain(config.not_found_overrides.values().map(PathBuf::as_path));
+        for path in not_found_files.chain(config.redirects.as_deref()).chain(config.gone_file.as_deref()).chain(std::iter::once(path)) {
+            let path: PathBuf = fs::canonicalize(path).unwrap_or_else(|_| path.into());
+            if path.starts_with(&config.site) {
+                debug!(target: CONFIG, "Protecting '{}' because it resides in the site directory", path.display());
+                protected.insert(path);
+            }
+        }
+        config.protected = protected;
+
         // Inject the server info and return
         config.name = name;
         config.version = version;
+        config.server_header = match HeaderValue::from_str(&format!("{name}/{version}")) {
+            Ok(value) => Some(value),
+            Err(err) => return Err(Error::IllegalServerHeader { name, version, err }),
+        };
         Ok(config)
     }
+
+    /// Reloads the redirects from the configured `redirects`-file.
+    ///
+    /// If loading fails, the current redirects are kept.
+    ///
+    /// # Returns
+    /// The number of redirects loaded, or [`None`] if no redirect file is configured.
+    ///
+    /// # Errors
+    /// This function errors if we failed to load the redirect file.
+    pub fn reload_redirects(&self) -> Result<Option<usize>, Error> {
+        let Some(path) = &self.redirects else { return Ok(None) };
+        let redirects: Redirects = match Redirects::load(path) {
+            Ok(redirects) => redirects,
+            Err(err) => return Err(Error::Redirects { err }),
+        };
+        let len: usize = redirects.len();
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        *self.redirect_map.write().unwrap() = redirects;
+        Ok(Some(len))
+    }
 }
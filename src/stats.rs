@@ -0,0 +1,281 @@
+//  STATS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 09:16:51
+//  Last edited:
+//    17 Oct 2026, 05:45:39
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Keeps track of lightweight server statistics, which can be dumped to
+//!   the log for quick introspection, and of the currently open
+//!   connections.
+//
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::Serialize;
+
+use crate::state::{Context, ReadLimiter};
+
+
+/***** HELPERS *****/
+/// What we know about an open connection.
+#[derive(Clone, Debug)]
+struct ConnectionInfo {
+    /// The address of the client (after applying the PROXY protocol, if any).
+    remote:    SocketAddr,
+    /// When the connection was opened.
+    opened:    Instant,
+    /// The number of requests received on the connection.
+    requests:  u64,
+    /// The path of the last request received on the connection, if any.
+    last_path: Option<String>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Identifies an open connection in the [`Stats`]. Given to every request as an extension.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ConnectionId(u64);
+
+/// Summarizes an open connection, as reported by the admin API.
+#[derive(Clone, Debug, Serialize)]
+pub struct ConnectionSummary {
+    /// The address of the client (after applying the PROXY protocol, if any).
+    pub remote: SocketAddr,
+    /// The number of seconds that the connection has been open.
+    pub duration_seconds: u64,
+    /// The number of requests received on the connection.
+    pub requests: u64,
+    /// The path of the last request received on the connection, if any.
+    pub last_path: Option<String>,
+}
+
+/// Summarizes the [`Stats`] since the server started, as reported by the admin API.
+#[derive(Clone, Debug, Serialize)]
+pub struct StatsSummary {
+    /// The number of seconds since the server started.
+    pub uptime_seconds: u64,
+    /// The number of connections that are currently open.
+    pub active_connections: usize,
+    /// The total number of requests answered.
+    pub requests: u64,
+    /// The number of responses sent per status class (1xx up to and including 5xx).
+    pub responses: [u64; 5],
+    /// The number of reads waiting for a permit, if `max_concurrent_reads` is set.
+    pub read_queue_depth: Option<usize>,
+}
+
+
+
+/// Counts connections, requests and responses since the server started.
+#[derive(Debug)]
+pub struct Stats {
+    /// When we started counting.
+    started: Instant,
+    /// The number of connections that are currently open.
+    active_connections: AtomicUsize,
+    /// The total number of requests answered.
+    requests: AtomicU64,
+    /// The number of responses sent per status class (1xx up to and including 5xx).
+    responses: [AtomicU64; 5],
+    /// The identifier given to the next connection.
+    next_id: AtomicU64,
+    /// The connections that are currently open.
+    connections: Mutex<HashMap<ConnectionId, ConnectionInfo>>,
+    /// The limiter of concurrent reads (if any), whose queue depth is reported.
+    read_limiter: Option<Arc<ReadLimiter>>,
+}
+impl Default for Stats {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            active_connections: AtomicUsize::new(0),
+            requests: AtomicU64::new(0),
+            responses: Default::default(),
+            next_id: AtomicU64::new(0),
+            connections: Mutex::new(HashMap::new()),
+            read_limiter: None,
+        }
+    }
+}
+impl Stats {
+    /// Constructor for the Stats.
+    ///
+    /// # Arguments
+    /// - `read_limiter`: The [`ReadLimiter`] (if any) whose queue depth is reported alongside the counts.
+    ///
+    /// # Returns
+    /// A new Stats that starts counting now.
+    #[inline]
+    pub fn new(read_limiter: Option<Arc<ReadLimiter>>) -> Self { Self { read_limiter, ..Self::default() } }
+
+    /// Registers a new connection, which is counted as active until the returned guard is dropped.
+    ///
+    /// # Arguments
+    /// - `remote`: The address of the client that opened the connection.
+    ///
+    /// # Returns
+    /// A [`ConnectionGuard`] that should live as long as the connection does.
+    pub fn connection(self: &Arc<Self>, remote: SocketAddr) -> ConnectionGuard {
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+        let id: ConnectionId = ConnectionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        self.connections.lock().unwrap().insert(id, ConnectionInfo { remote, opened: Instant::now(), requests: 0, last_path: None });
+        ConnectionGuard { stats: self.clone(), id }
+    }
+
+    /// Registers a request received on the given connection.
+    ///
+    /// # Arguments
+    /// - `id`: The [`ConnectionId`] of the connection on which the request was received.
+    /// - `path`: The path of the request.
+    pub fn request(&self, id: ConnectionId, path: &str) {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        if let Some(info) = self.connections.lock().unwrap().get_mut(&id) {
+            info.requests += 1;
+            info.last_path = Some(path.into());
+        }
+    }
+
+    /// Registers a response with the given status code.
+    ///
+    /// # Arguments
+    /// - `code`: The status code of the response.
+    #[inline]
+    pub fn response(&self, code: u16) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if let Some(count) = self.responses.get(usize::from(code / 100).wrapping_sub(1)) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Summarizes the connections that are currently open.
+    ///
+    /// # Returns
+    /// A [`ConnectionSummary`] for every open connection, the oldest first.
+    pub fn connections(&self) -> Vec<ConnectionSummary> {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        let connections = self.connections.lock().unwrap();
+        let mut infos: Vec<&ConnectionInfo> = connections.values().collect();
+        infos.sort_by_key(|info| info.opened);
+        infos
+            .into_iter()
+            .map(|info| ConnectionSummary {
+                remote: info.remote,
+                duration_seconds: info.opened.elapsed().as_secs(),
+                requests: info.requests,
+                last_path: info.last_path.clone(),
+            })
+            .collect()
+    }
+
+    /// Summarizes the counts so far.
+    ///
+    /// # Returns
+    /// A [`StatsSummary`] of the counts, as they are now.
+    pub fn summary(&self) -> StatsSummary {
+        StatsSummary {
+            uptime_seconds: self.started.elapsed().as_secs(),
+            active_connections: self.active_connections.load(Ordering::Relaxed),
+            requests: self.requests.load(Ordering::Relaxed),
+            responses: std::array::from_fn(|i| self.responses[i].load(Ordering::Relaxed)),
+            read_queue_depth: self.read_limiter.as_ref().map(|limiter| limiter.queue_depth()),
+        }
+    }
+}
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        let uptime: u64 = self.started.elapsed().as_secs();
+        write!(
+            f,
+            "uptime {}d {:02}:{:02}:{:02}, {} active connection(s), {} request(s) (",
+            uptime / 86400,
+            (uptime / 3600) % 24,
+            (uptime / 60) % 60,
+            uptime % 60,
+            self.active_connections.load(Ordering::Relaxed),
+            self.requests.load(Ordering::Relaxed),
+        )?;
+        for (i, count) in self.responses.iter().enumerate() {
+            write!(f, "{}{}xx: {}", if i > 0 { ", " } else { "" }, i + 1, count.load(Ordering::Relaxed))?;
+        }
+        write!(f, ")")?;
+        if let Some(limiter) = &self.read_limiter {
+            write!(f, ", {} read(s) queued", limiter.queue_depth())?;
+        }
+        Ok(())
+    }
+}
+
+
+
+/// Keeps a connection counted as active in the [`Stats`] until it is dropped.
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    /// The stats in which the connection is counted.
+    stats: Arc<Stats>,
+    /// The identifier of the connection.
+    id:    ConnectionId,
+}
+impl ConnectionGuard {
+    /// Returns the identifier of this connection, which should be given to its requests.
+    ///
+    /// # Returns
+    /// The [`ConnectionId`] of this connection.
+    #[inline]
+    pub fn id(&self) -> ConnectionId { self.id }
+
+    /// Updates the address of the client, e.g., once it is known from a PROXY protocol header.
+    ///
+    /// # Arguments
+    /// - `remote`: The (real) address of the client.
+    pub fn set_remote(&self, remote: SocketAddr) {
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        if let Some(info) = self.stats.connections.lock().unwrap().get_mut(&self.id) {
+            info.remote = remote;
+        }
+    }
+}
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.stats.active_connections.fetch_sub(1, Ordering::Relaxed);
+        // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+        self.stats.connections.lock().unwrap().remove(&self.id);
+    }
+}
+
+
+
+/// Counts every request and the status class of its response, and registers the request with its connection (if known).
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that holds the [`Stats`].
+/// - `request`: The incoming request.
+/// - `next`: The rest of the middleware stack.
+///
+/// # Returns
+/// The response of the rest of the stack, untouched.
+pub async fn record(State(state): State<Arc<Context>>, request: Request, next: Next) -> Response {
+    if let Some(id) = request.extensions().get::<ConnectionId>() {
+        state.stats.request(*id, request.uri().path());
+    }
+    let res: Response = next.run(request).await;
+    state.stats.response(res.status().as_u16());
+    res
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Jul 2024, 18:59:49
 //  Last edited:
-//    17 Jul 2024, 19:48:11
+//    17 Oct 2026, 11:28:00
 //  Auto updated?
 //    Yes
 //
@@ -12,129 +12,1978 @@
 //!   Provides an axum path for hosting static files in some folder.
 //
 
-use std::ffi::OsStr;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Arguments, Write as _};
+use std::future::Future;
+use std::io::{Cursor, SeekFrom};
 use std::path::{Component, Path, PathBuf};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use axum::body::{Body, Bytes};
+use axum::extract::path::ErrorKind;
+use axum::extract::rejection::PathRejection;
 use axum::extract::{self, State};
-use axum::http::HeaderValue;
+use axum::http::{HeaderName, HeaderValue};
 use axum_extra::body::AsyncReadBody;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
 use error_trace::trace;
-use hyper::{header, HeaderMap, StatusCode};
-use log::{debug, error, info};
+use hyper::{header, HeaderMap, Method, StatusCode, Uri};
+use log::{debug, error, info, log, warn};
 use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncSeekExt as _, AsyncWriteExt as _, DuplexStream, ReadBuf};
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::time::Sleep;
+
+use crate::livereload;
+use crate::logging::WWW;
+use crate::signed::{self, Signature};
+use crate::sri::SriCache;
+use crate::state::{
+    ArtificialDelay, Context, CspNonce, EtagStrategy, LanguageNegotiation, LineEnding, NotFoundFile, ParentDirPolicy, Resolution,
+};
+
+
+/***** CONSTANTS *****/
+/// The built-in mapping of file extensions to MIME types.
+pub const MIME_TYPES: &[(&str, &str)] = &[
+    ("br", "application/x-brotli"),
+    ("css", "text/css"),
+    ("gz", "application/gzip"),
+    ("html", "text/html"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("svg", "image/svg+xml"),
+    ("tar", "application/x-tar"),
+    ("tgz", "application/gzip"),
+    ("zip", "application/zip"),
+];
+/// The MIME type of files with an extension that isn't known.
+pub const DEFAULT_MIME_TYPE: &str = "text/plain";
+/// The signatures at the start of files that identify their MIME type, for files with an unknown extension.
+const MAGIC_BYTES: [(&[u8], &str); 6] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xFF\xD8\xFF", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"\x1F\x8B", "application/gzip"),
+];
+
+/// The OS error code for when the process has too many open files.
+const EMFILE: i32 = 24;
+/// The OS error code for when the system has too many open files.
+const ENFILE: i32 = 23;
+/// The OS error code for a generic I/O error (e.g., a hiccup of a network filesystem).
+const EIO: i32 = 5;
+/// The OS error code for when an NFS file handle has gone stale.
+const ESTALE: i32 = 116;
+
+/// The maximum number of bytes read at once from bodies with a bandwidth limit.
+const THROTTLE_CHUNK: usize = 16 * 1024;
+
+/// The maximum number of ranges in a single `Range`-header. Requests for more are sent the whole file instead.
+const MAX_RANGES: usize = 32;
+/// The largest gap (in bytes) between two requested ranges that are still merged into one, which is about the size of a part's headers.
+const RANGE_MERGE_GAP: u64 = 80;
+
+/// The abbreviated names of the days of the week, starting at Sunday.
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+/// The abbreviated names of the months, starting at January.
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// The maximum number of bytes at the start of a textual file that are sniffed for its charset.
+const SNIFF_LEN: usize = 1024;
+/// The maximum number of bytes at the start of a file with an unknown extension that are sniffed for its MIME type.
+const CONTENT_SNIFF_LEN: usize = 512;
+
+/// The delay before the first retry of a transient filesystem error. Doubles with each retry.
+const FS_RETRY_BASE: Duration = Duration::from_millis(10);
+/// The maximum delay between two retries of a transient filesystem error.
+const FS_RETRY_CAP: Duration = Duration::from_millis(200);
+
+
+
+
+
+/***** HELPERS *****/
+/// Wraps an [`AsyncRead`] such that it holds on to a read permit (if any) until the body is done.
+struct Permitted<R> {
+    /// The reader to read from.
+    reader:  R,
+    /// The permit that we hold while reading.
+    _permit: Option<OwnedSemaphorePermit>,
+}
+impl<R: AsyncRead + Unpin> AsyncRead for Permitted<R> {
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.reader).poll_read(cx, buf)
+    }
+}
+
+/// Wraps an [`AsyncRead`] such that it is read no faster than a given rate, using a token bucket that holds up to a second's worth.
+struct Throttled<R> {
+    /// The reader to read from.
+    reader:  R,
+    /// The maximum number of bytes per second.
+    rate:    u64,
+    /// The number of bytes that may currently be read.
+    tokens:  f64,
+    /// When the tokens were last refilled.
+    last:    Instant,
+    /// The timer we're waiting on for new tokens, if any.
+    sleep:   Option<Pin<Box<Sleep>>>,
+    /// A buffer to read into, such that we never read more than we have tokens for.
+    scratch: Vec<u8>,
+}
+impl<R> Throttled<R> {
+    /// Constructor for the Throttled that starts with a full bucket.
+    ///
+    /// # Arguments
+    /// - `reader`: The reader to read from.
+    /// - `rate`: The maximum number of bytes per second. Must be positive.
+    ///
+    /// # Returns
+    /// A new Throttled that reads from `reader` at most at `rate`.
+    #[inline]
+    fn new(reader: R, rate: u64) -> Self {
+        Self { reader, rate, tokens: rate as f64, last: Instant::now(), sleep: None, scratch: Vec::new() }
+    }
+}
+impl<R: AsyncRead + Unpin> AsyncRead for Throttled<R> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let this: &mut Self = &mut *self;
+        loop {
+            if let Some(sleep) = &mut this.sleep {
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.sleep = None;
+            }
+
+            // Refill the bucket for the time that has passed
+            let now: Instant = Instant::now();
+            this.tokens = (this.tokens + now.duration_since(this.last).as_secs_f64() * this.rate as f64).min(this.rate as f64);
+            this.last = now;
+            if this.tokens >= 1.0 {
+                break;
+            }
+            // NOTE: Wait for a reasonable chunk rather than a single byte, so slow rates don't cause a wakeup per byte
+            let wanted: f64 = (this.rate as f64).min(THROTTLE_CHUNK as f64);
+            this.sleep = Some(Box::pin(tokio::time::sleep(Duration::from_secs_f64((wanted - this.tokens) / this.rate as f64))));
+        }
+
+        // Read no more than we have tokens for
+        let allowed: usize = (this.tokens as usize).min(buf.remaining()).min(THROTTLE_CHUNK);
+        this.scratch.resize(allowed, 0);
+        let mut scratch: ReadBuf<'_> = ReadBuf::new(&mut this.scratch);
+        match Pin::new(&mut this.reader).poll_read(cx, &mut scratch) {
+            Poll::Ready(Ok(())) => {
+                let read: &[u8] = scratch.filled();
+                this.tokens -= read.len() as f64;
+                buf.put_slice(read);
+                Poll::Ready(Ok(()))
+            },
+            res => res,
+        }
+    }
+}
+
+
 
-use crate::state::Context;
 
 
 /***** HELPER FUNCTIONS *****/
+/// Wraps a reader in a streaming body, which holds on to a read permit (if any) and is throttled to a bandwidth limit (if any).
+///
+/// # Arguments
+/// - `reader`: The reader to stream.
+/// - `permit`: The read permit (if any) to hold on to while the body is being read.
+/// - `limit`: The maximum number of bytes per second to stream, if any.
+///
+/// # Returns
+/// A [`Body`] streaming `reader`.
+fn stream_body(reader: impl AsyncRead + Send + Unpin + 'static, permit: Option<OwnedSemaphorePermit>, limit: Option<u64>) -> Body {
+    match limit {
+        Some(rate) => Body::new(AsyncReadBody::new(Permitted { reader: Throttled::new(reader, rate), _permit: permit })),
+        None => Body::new(AsyncReadBody::new(Permitted { reader, _permit: permit })),
+    }
+}
+
+/// Extracts the request path matched by the router.
+///
+/// # Arguments
+/// - `path`: The result of extracting the path, which is missing for the root.
+///
+/// # Returns
+/// The decoded request path, relative to the site root.
+///
+/// # Errors
+/// This function returns 400 BAD REQUEST if the decoded path is not valid UTF-8 (e.g., because of overlong encodings) or contains NUL
+/// bytes, neither of which can name a file.
+fn request_path(path: Result<extract::Path<PathBuf>, PathRejection>) -> Result<PathBuf, StatusCode> {
+    match path {
+        Ok(extract::Path(path)) if path.as_os_str().as_encoded_bytes().contains(&0) => {
+            debug!(target: WWW, "[400] Request path contains a NUL byte");
+            Err(StatusCode::BAD_REQUEST)
+        },
+        Ok(extract::Path(path)) => Ok(path),
+        // The root route doesn't have a path to extract
+        Err(PathRejection::MissingPathParams(_)) => Ok(PathBuf::new()),
+        Err(PathRejection::FailedToDeserializePathParams(err)) if matches!(err.kind(), ErrorKind::WrongNumberOfParameters { .. }) => {
+            Ok(PathBuf::new())
+        },
+        Err(err) => {
+            debug!(target: WWW, "{}", trace!(("[400] Request path is not a valid path"), err));
+            Err(StatusCode::BAD_REQUEST)
+        },
+    }
+}
+
+/// Runs a filesystem operation, retrying it if it fails with a transient error (`EIO` or `ESTALE`).
+///
+/// Any other error (e.g., not found or permission denied) is returned immediately.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that determines how often we retry with its `fs_retries`.
+/// - `op`: A closure that starts the operation anew.
+///
+/// # Returns
+/// The result of the first attempt that didn't fail transiently, or the last error if we ran out of retries.
+async fn with_fs_retries<T, F: Future<Output = std::io::Result<T>>>(state: &Context, mut op: impl FnMut() -> F) -> std::io::Result<T> {
+    let mut retry: u32 = 0;
+    loop {
+        match op().await {
+            Err(err) if retry < state.fs_retries && matches!(err.raw_os_error(), Some(EIO | ESTALE)) => {
+                // Back off exponentially, with some jitter to avoid retrying in lockstep
+                let delay: Duration = FS_RETRY_BASE.saturating_mul(1 << retry.min(16)).min(FS_RETRY_CAP);
+                let nanos: u32 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+                let delay: Duration = delay / 2 + Duration::from_nanos(u64::from(nanos) % (delay.as_nanos() as u64 / 2 + 1));
+                retry += 1;
+                debug!(
+                    target: WWW,
+                    "Transient filesystem error ({err}); retrying in {}ms ({retry}/{})...",
+                    delay.as_millis(),
+                    state.fs_retries
+                );
+                tokio::time::sleep(delay).await;
+            },
+            res => return res,
+        }
+    }
+}
+
+/// Looks up the MIME type of a file based on its extension.
+///
+/// Note that archives (e.g., `.gz` or `.zip`) are served as what they are, i.e., they never get a `Content-Encoding`.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that may override the built-in [`MIME_TYPES`].
+/// - `ext`: The extension of the file (without leading dot), if any.
+///
+/// # Returns
+/// The MIME type of the file, or [`None`] if we don't recognize the extension.
+#[inline]
+fn lookup_mime<'s>(state: &'s Context, ext: Option<&str>) -> Option<&'s str> {
+    ext.and_then(|ext| {
+        state.mime_types.get(ext).map(String::as_str).or_else(|| MIME_TYPES.iter().find(|(known, _)| *known == ext).map(|(_, mime)| *mime))
+    })
+}
+
+/// Guesses the MIME type of a file based on its extension.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that may override the built-in [`MIME_TYPES`].
+/// - `ext`: The extension of the file (without leading dot), if any.
+///
+/// # Returns
+/// The MIME type of the file, which is [`DEFAULT_MIME_TYPE`] if we don't recognize the extension.
+#[inline]
+fn guess_mime<'s>(state: &'s Context, ext: Option<&str>) -> &'s str { lookup_mime(state, ext).unwrap_or(DEFAULT_MIME_TYPE) }
+
+/// Sniffs the MIME type of a file from the signature in its first bytes.
+///
+/// # Arguments
+/// - `head`: The first (at most [`CONTENT_SNIFF_LEN`]) bytes of the file.
+///
+/// # Returns
+/// The MIME type of the first of the [`MAGIC_BYTES`] that `head` starts with, or [`None`] if it starts with none.
+#[inline]
+fn sniff_content(head: &[u8]) -> Option<&'static str> {
+    MAGIC_BYTES.iter().find(|(magic, _)| head.starts_with(magic)).map(|(_, mime)| *mime)
+}
+
+/// Decides whether a MIME type describes textual content, i.e., content that should carry a charset.
+///
+/// # Arguments
+/// - `mime`: The MIME type to check.
+///
+/// # Returns
+/// True for `text/*`, `application/javascript`, `application/json` and `image/svg+xml`; false for everything else.
+#[inline]
+fn is_textual(mime: &str) -> bool {
+    mime.starts_with("text/") || matches!(mime, "application/javascript" | "application/json" | "image/svg+xml")
+}
+
+/// Sniffs the charset of a textual file from its first bytes.
+///
+/// Looks for a byte order mark, an XML declaration (`<?xml ... encoding="..."?>`) and HTML `<meta>`-tags declaring a charset.
+///
+/// # Arguments
+/// - `head`: The first (at most [`SNIFF_LEN`]) bytes of the file.
+///
+/// # Returns
+/// The (lowercase) charset if one was declared, or [`None`] if none was or if the declarations disagree.
+fn sniff_charset(head: &[u8]) -> Option<String> {
+    // A byte order mark always wins
+    if head.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Some("utf-8".into());
+    } else if head.starts_with(&[0xFF, 0xFE]) {
+        return Some("utf-16le".into());
+    } else if head.starts_with(&[0xFE, 0xFF]) {
+        return Some("utf-16be".into());
+    }
+
+    // Otherwise, look for declarations of it
+    let text: String = String::from_utf8_lossy(head).to_ascii_lowercase();
+    let value_of = |decl: &str, attr: &str| -> Option<String> {
+        let decl: String = decl.split_ascii_whitespace().collect::<Vec<&str>>().join(" ").replace(" =", "=").replace("= ", "=");
+        let value: &str = decl.split(attr).nth(1)?.trim_start_matches(['"', '\'']);
+        let value: String = value.chars().take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':')).collect();
+        if value.is_empty() { None } else { Some(value) }
+    };
+    let mut declared: Vec<String> = Vec::new();
+    if let Some(decl) = text.strip_prefix("<?xml").and_then(|rest| rest.split("?>").next()) {
+        declared.extend(value_of(decl, "encoding="));
+    }
+    for tag in text.split("<meta").skip(1).filter_map(|rest| rest.split('>').next()) {
+        declared.extend(value_of(tag, "charset="));
+    }
+
+    // Only trust them if they agree
+    let first: String = declared.first()?.clone();
+    if declared.iter().all(|charset| *charset == first) { Some(first) } else { None }
+}
+
+/// Checks whether a request may access a path, which it may unless the path requires a signed URL and the request doesn't carry a valid
+/// signature.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that defines which paths require signed URLs.
+/// - `uri`: The target of the incoming request, which carries the signature.
+/// - `path`: The path to check, relative to the site root.
+///
+/// # Returns
+/// True if the request may proceed, false if it should be refused with 403 FORBIDDEN.
+fn has_valid_signature(state: &Context, uri: &Uri, path: &Path) -> bool {
+    let Some(signed) = state.signed_urls.as_ref().filter(|signed| signed.matcher.find(path).is_some()) else { return true };
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    match signed::check(&signed.secret, uri.path(), uri.query(), now) {
+        Signature::Valid => true,
+        refusal => {
+            debug!(
+                target: WWW,
+                "[403] Request for signed path '{}' has a missing, invalid or expired signature ({refusal:?})",
+                path.display()
+            );
+            false
+        },
+    }
+}
+
+/// Applies the configured transformations to the contents of a textual file.
+///
+/// # Arguments
+/// - `data`: The contents of the file, in an ASCII-compatible charset.
+/// - `endings`: The [`LineEnding`] to normalize the line endings to, if any.
+/// - `strip_bom`: Whether to strip a leading UTF-8 byte order mark.
+///
+/// # Returns
+/// The transformed contents.
+fn transform_text(data: &[u8], endings: Option<LineEnding>, strip_bom: bool) -> Vec<u8> {
+    let data: &[u8] = if strip_bom { data.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(data) } else { data };
+    let mut res: Vec<u8> = Vec::with_capacity(data.len());
+    for (i, b) in data.iter().enumerate() {
+        match (endings, *b) {
+            (Some(LineEnding::Lf), b'\r') if data.get(i + 1) == Some(&b'\n') => continue,
+            (Some(LineEnding::Crlf), b'\n') if i == 0 || data[i - 1] != b'\r' => res.extend_from_slice(b"\r\n"),
+            (_, b) => res.push(b),
+        }
+    }
+    res
+}
+
+/// Replaces all occurrences of a pattern in some bytes.
+///
+/// # Arguments
+/// - `data`: The bytes to replace in.
+/// - `from`: The (non-empty) pattern to replace.
+/// - `to`: The bytes to replace it with.
+///
+/// # Returns
+/// A copy of `data` with every (non-overlapping) occurrence of `from` replaced by `to`.
+fn replace_bytes(data: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    let mut res: Vec<u8> = Vec::with_capacity(data.len());
+    let mut i: usize = 0;
+    while i < data.len() {
+        if !from.is_empty() && data[i..].starts_with(from) {
+            res.extend_from_slice(to);
+            i += from.len();
+        } else {
+            res.push(data[i]);
+            i += 1;
+        }
+    }
+    res
+}
+
+/// Inserts a snippet before the closing `</body>`-tag of an HTML document.
+///
+/// # Arguments
+/// - `html`: The HTML document to insert in.
+/// - `snippet`: The bytes to insert.
+///
+/// # Returns
+/// A copy of `html` with `snippet` inserted before its last `</body>`-tag (matched case-insensitively), or at the end if it has none.
+fn insert_before_body_end(html: &[u8], snippet: &[u8]) -> Vec<u8> {
+    const BODY_END: &[u8] = b"</body";
+    let pos: usize = html.windows(BODY_END.len()).rposition(|window| window.eq_ignore_ascii_case(BODY_END)).unwrap_or(html.len());
+    let mut res: Vec<u8> = Vec::with_capacity(html.len() + snippet.len());
+    res.extend_from_slice(&html[..pos]);
+    res.extend_from_slice(snippet);
+    res.extend_from_slice(&html[pos..]);
+    res
+}
+
+/// Generates a fresh nonce for a `Content-Security-Policy`.
+///
+/// # Returns
+/// 128 random bits, encoded as base64.
+///
+/// # Errors
+/// This function errors if the operating system failed to provide any randomness.
+fn generate_nonce() -> Result<String, getrandom::Error> {
+    let mut bytes: [u8; 16] = [0; 16];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(BASE64.encode(bytes))
+}
+
+/// Computes the `ETag` of a file from its length and modification time.
+///
+/// # Arguments
+/// - `len`: The length of the file.
+/// - `modified`: The modification time of the file (since the UNIX epoch), if known.
+///
+/// # Returns
+/// A (strong) `ETag`, including quotes.
+#[inline]
+fn mtime_size_etag(len: u64, modified: Option<Duration>) -> String {
+    format!("\"{len:x}-{:x}\"", modified.map(|modified| modified.as_nanos()).unwrap_or(0))
+}
+
+/// Picks the artificial delay for a request.
+///
+/// # Arguments
+/// - `delay`: The [`ArtificialDelay`] that defines which paths are delayed, and by how much.
+/// - `path`: The request path, relative to the site root.
+///
+/// # Returns
+/// A delay picked at random from the configured range, or [`None`] if `path` isn't delayed.
+fn artificial_delay(delay: &ArtificialDelay, path: &Path) -> Option<Duration> {
+    if !delay.paths.is_empty() && delay.matcher.find(path).is_none() {
+        return None;
+    }
+    let span: u64 = delay.max_ms.unwrap_or(delay.min_ms).saturating_sub(delay.min_ms);
+    let mut bytes: [u8; 8] = [0; 8];
+    // NOTE: Without randomness, we just don't jitter
+    let offset: u64 =
+        if span > 0 && getrandom::getrandom(&mut bytes).is_ok() { u64::from_le_bytes(bytes) % span.saturating_add(1) } else { 0 };
+    Some(Duration::from_millis(delay.min_ms + offset))
+}
+
+/// Checks whether a request comes from a crawler.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the (lowercase) `bot_user_agents`.
+/// - `headers`: The headers of the request.
+///
+/// # Returns
+/// True if its `User-Agent` contains any of the `bot_user_agents` (ignoring case), false otherwise.
+fn is_bot(state: &Context, headers: &HeaderMap) -> bool {
+    let Some(agent) = headers.get(header::USER_AGENT).and_then(|value| value.to_str().ok()) else { return false };
+    let agent: String = agent.to_ascii_lowercase();
+    state.bot_user_agents.iter().any(|bot| agent.contains(bot.as_str()))
+}
+
+/// Logically normalizes a request path, resolving any `.` and `..` components without touching the filesystem.
+///
+/// # Arguments
+/// - `path`: The path as requested by the user.
+/// - `policy`: The [`ParentDirPolicy`] that determines what to do with `..` components.
+///
+/// # Returns
+/// The normalized path, relative to the site root.
+///
+/// # Errors
+/// This function errors with a status code to return if the path would go above the site root (404 NOT FOUND), or if it contains any `..` when
+/// the `policy` is [`ParentDirPolicy::Reject`] (400 BAD REQUEST).
+fn normalize_path(path: &Path, policy: ParentDirPolicy) -> Result<PathBuf, StatusCode> {
+    let mut res: PathBuf = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {},
+            Component::ParentDir => {
+                if policy == ParentDirPolicy::Reject {
+                    debug!(target: WWW, "[400] Request path '{}' contains parent directory components", path.display());
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+                if !res.pop() {
+                    return Err(StatusCode::NOT_FOUND);
+                }
+            },
+            Component::Normal(part) => res.push(part),
+        }
+    }
+    Ok(res)
+}
+
+/// Parses an `Accept`-like header (e.g., `Accept` or `Accept-Language`) into its ranges, ordered by descending quality.
+///
+/// # Arguments
+/// - `value`: The raw value of the header.
+///
+/// # Returns
+/// A list of ranges the client accepts, most-preferred first. Ranges with a quality of 0 are omitted.
+fn parse_accept(value: &str) -> Vec<&str> {
+    let mut ranges: Vec<(&str, f32)> = value
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let lang: &str = parts.next()?.trim();
+            if lang.is_empty() {
+                return None;
+            }
+            let quality: f32 = parts
+                .find_map(|param| param.trim().strip_prefix("q=").map(|q| q.trim().parse::<f32>().unwrap_or(0.0)))
+                .unwrap_or(1.0);
+            if quality > 0.0 { Some((lang, quality)) } else { None }
+        })
+        .collect();
+    // NOTE: Stable sort, so equal qualities keep the order the client gave them in
+    ranges.sort_by(|(_, lhs), (_, rhs)| rhs.total_cmp(lhs));
+    ranges.into_iter().map(|(lang, _)| lang).collect()
+}
+
+/// Checks whether a client's language range matches an available language.
+///
+/// Matching is case-insensitive, and either side may be a prefix of the other (e.g., `en` matches `en-GB` and vice versa).
+///
+/// # Arguments
+/// - `range`: The language range as given by the client.
+/// - `lang`: The available language to match.
+///
+/// # Returns
+/// True if the range matches the language, false otherwise.
+fn language_matches(range: &str, lang: &str) -> bool {
+    fn is_prefix(prefix: &str, of: &str) -> bool {
+        of.len() > prefix.len() && of.as_bytes()[prefix.len()] == b'-' && of[..prefix.len()].eq_ignore_ascii_case(prefix)
+    }
+    range == "*" || range.eq_ignore_ascii_case(lang) || is_prefix(range, lang) || is_prefix(lang, range)
+}
+
+/// Checks whether every component of a path is spelled exactly like the directory entry it resolves to.
+///
+/// This matters on case-insensitive filesystems, where e.g. `INDEX.HTML` opens `index.html`.
+///
+/// # Arguments
+/// - `root`: The directory in which `path` starts.
+/// - `path`: The (normalized) relative path to check.
+///
+/// # Returns
+/// True if every component matches an entry of its parent directory byte-for-byte, false otherwise (including if a directory cannot be read).
+fn has_exact_case(root: &Path, path: &Path) -> bool {
+    let mut dir: PathBuf = root.into();
+    for comp in path.components() {
+        let Component::Normal(name) = comp else { continue };
+        let found: bool = match std::fs::read_dir(&dir) {
+            Ok(mut entries) => entries.any(|entry| entry.is_ok_and(|entry| entry.file_name() == name)),
+            Err(_) => false,
+        };
+        if !found {
+            return false;
+        }
+        dir.push(name);
+    }
+    true
+}
+
+/// Builds the absolute URL path of a directory, to redirect to.
+///
+/// This is built from the normalized path instead of the request's own path, such that it always stays on the same origin (e.g., a
+/// request for `//example.com/index.html` is redirected to `/example.com/` instead of to another host).
+///
+/// # Arguments
+/// - `dir`: The (normalized) path of the directory, relative to the site root.
+///
+/// # Returns
+/// The path of `dir` with a leading and trailing slash, and every component percent-encoded again.
+fn dir_location(dir: &Path) -> String {
+    let mut res: String = String::from("/");
+    for comp in dir.components() {
+        let Component::Normal(name) = comp else { continue };
+        for byte in name.as_encoded_bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(byte) {
+                res.push(*byte as char);
+            } else {
+                let _ = write!(res, "%{byte:02X}");
+            }
+        }
+        res.push('/');
+    }
+    res
+}
+
+/// Finds the index files that apply to the given directory.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the global `index_files` and the per-pattern `index_overrides`.
+/// - `dir`: The (normalized) request path of the directory, relative to the site root.
+///
+/// # Returns
+/// The index files of the most specific pattern in `index_overrides` that matches `dir`, or the global `index_files` if none does.
+#[inline]
+fn index_files<'s>(state: &'s Context, dir: &Path) -> &'s [String] {
+    state.index_matcher.find(dir).map(Vec::as_slice).unwrap_or(&state.index_files)
+}
+
+/// Finds the files that a request path resolves to with both the `html_suffix` and `dir_index` resolution steps.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the site directory and index files.
+/// - `path`: The (normalized) request path, relative to the site root.
+///
+/// # Returns
+/// The `<path>.html` file and the index file of the `<path>/` directory if both exist, or [`None`] otherwise.
+fn clean_url_collision(state: &Context, path: &Path) -> Option<(PathBuf, PathBuf)> {
+    if path.as_os_str().is_empty() {
+        return None;
+    }
+    let mut suffixed: OsString = state.site.join(path).into_os_string();
+    suffixed.push(".html");
+    let suffixed: PathBuf = suffixed.into();
+    let dir: PathBuf = state.site.join(path);
+    if !suffixed.is_file() || !dir.is_dir() {
+        return None;
+    }
+    let index: PathBuf = index_files(state, path).iter().map(|name| dir.join(name)).find(|index| index.is_file())?;
+    Some((suffixed, index))
+}
+
+/// Decides whether an explicitly requested index file may be redirected to its directory, i.e., whether the directory is served as that
+/// exact file.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] with the site directory, index files and resolution order.
+/// - `path`: The (normalized) request path of the index file, relative to the site root.
+/// - `accept_language`: The value of the client's `Accept-Language`-header, if any.
+///
+/// # Returns
+/// True if requesting the directory resolves to the same file, false otherwise (e.g., because another index file or a language variant
+/// takes precedence, the directory is resolved by another step or not at all, or `/` is served as the `root_file`).
+fn index_redirects(state: &Context, path: &Path, accept_language: Option<&str>) -> bool {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else { return false };
+    if dir.as_os_str().is_empty() && state.root_file.is_some() {
+        return false;
+    }
+
+    // The directory must be found by its index file, and not by an earlier step
+    let Some(pos) = state.resolution_order.iter().position(|step| *step == Resolution::DirIndex) else { return false };
+    if !dir.as_os_str().is_empty() && state.resolution_order[..pos].contains(&Resolution::HtmlSuffix) {
+        let mut suffixed: OsString = state.site.join(dir).into_os_string();
+        suffixed.push(".html");
+        if Path::new(&suffixed).is_file() {
+            return false;
+        }
+    }
+    let Ok(dir_path) = std::fs::canonicalize(state.site.join(dir)) else { return false };
+    if !dir_path.starts_with(&state.site) || !dir_path.is_dir() {
+        return false;
+    }
+    if !std::fs::canonicalize(state.site.join(path)).is_ok_and(|file| file.starts_with(&state.site) && file.is_file()) {
+        return false;
+    }
+
+    // Then it must pick this file, the same way the `dir_index` step does
+    let variant: Option<PathBuf> = state
+        .language_negotiation
+        .as_ref()
+        .and_then(|negotiation| negotiate_index(negotiation, &dir_path, accept_language))
+        .map(|(path, _)| path);
+    let served: Option<PathBuf> =
+        variant.or_else(|| index_files(state, dir).iter().map(|index| dir_path.join(index)).find(|index| index.is_file()));
+    served.is_some_and(|served| served == dir_path.join(name))
+}
+
+/// Selects the not found file that best fits the client.
+///
+/// # Arguments
+/// - `files`: The not found files per MIME type, which is assumed to include one for `text/html`.
+/// - `accept`: The value of the client's `Accept`-header, if any.
+///
+/// # Returns
+/// The MIME type and the path of the selected file. This is the `text/html` one if nothing else is accepted.
+fn negotiate_not_found<'f>(files: &'f BTreeMap<String, PathBuf>, accept: Option<&str>) -> (&'f str, &'f Path) {
+    for range in accept.map(parse_accept).unwrap_or_default() {
+        // Wildcards match anything, so they're equally happy with our default
+        let (kind, subtype): (&str, &str) = range.split_once('/').unwrap_or((range, "*"));
+        if kind == "*" {
+            break;
+        }
+        let found = files.iter().find(|(mime, _)| {
+            let (mkind, msubtype): (&str, &str) = mime.split_once('/').unwrap_or((mime.as_str(), ""));
+            mkind.eq_ignore_ascii_case(kind) && (subtype == "*" || msubtype.eq_ignore_ascii_case(subtype))
+        });
+        if let Some((mime, path)) = found {
+            return (mime.as_str(), path.as_path());
+        }
+    }
+    // SAFETY: The config is checked to have an HTML not found file when it is loaded
+    files.get_key_value("text/html").map(|(mime, path)| (mime.as_str(), path.as_path())).unwrap()
+}
+
+/// Selects the language variant of the index file in the given directory that best fits the client.
+///
+/// # Arguments
+/// - `negotiation`: The [`LanguageNegotiation`] that describes the available languages.
+/// - `dir`: The directory for which to find an index file.
+/// - `accept`: The value of the client's `Accept-Language` header, if any.
+///
+/// # Returns
+/// The path to the `index.<lang>.html` file that was selected and the language it was selected for, or [`None`] if no variant exists for any of
+/// the acceptable languages (including the default).
+fn negotiate_index(negotiation: &LanguageNegotiation, dir: &Path, accept: Option<&str>) -> Option<(PathBuf, String)> {
+    // Collect the candidate languages in order of preference
+    let mut candidates: Vec<&str> = Vec::new();
+    for range in accept.map(parse_accept).unwrap_or_default() {
+        for lang in &negotiation.languages {
+            if language_matches(range, lang) && !candidates.contains(&lang.as_str()) {
+                candidates.push(lang);
+            }
+        }
+    }
+    if let Some(default) = &negotiation.default {
+        candidates.push(default);
+    }
+
+    // Take the first one that exists
+    candidates.into_iter().find_map(|lang| {
+        let path: PathBuf = dir.join(format!("index.{lang}.html"));
+        if path.is_file() { Some((path, lang.into())) } else { None }
+    })
+}
+
+
+/// Formats a point in time as an HTTP-date (RFC 9110, § 5.6.7), e.g., `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// # Arguments
+/// - `secs`: The number of seconds since the UNIX epoch.
+///
+/// # Returns
+/// The formatted date.
+fn http_date(secs: u64) -> String {
+    let (days, rem): (u64, u64) = (secs / 86400, secs % 86400);
+
+    // Convert the days to a civil date (see <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>)
+    let z: u64 = days + 719468;
+    let (era, doe): (u64, u64) = (z / 146097, z % 146097);
+    let yoe: u64 = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy: u64 = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp: u64 = (5 * doy + 2) / 153;
+    let day: u64 = doy - (153 * mp + 2) / 5 + 1;
+    let month: u64 = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year: u64 = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{}, {day:02} {} {year} {:02}:{:02}:{:02} GMT",
+        // NOTE: 1 January 1970 was a Thursday
+        WEEKDAYS[((days + 4) % 7) as usize],
+        MONTHS[(month - 1) as usize],
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// Parses an HTTP-date in the preferred format (RFC 9110, § 5.6.7), e.g., `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// # Arguments
+/// - `value`: The date to parse.
+///
+/// # Returns
+/// The number of seconds since the UNIX epoch, or [`None`] if the date is malformed or before the epoch.
+fn parse_http_date(value: &str) -> Option<u64> {
+    let parts: Vec<&str> = value.split_ascii_whitespace().collect();
+    let [weekday, day, month, year, time, "GMT"] = parts.as_slice() else { return None };
+    if !weekday.strip_suffix(',').is_some_and(|weekday| WEEKDAYS.contains(&weekday)) || day.len() != 2 || year.len() != 4 {
+        return None;
+    }
+    let day: u64 = day.parse().ok().filter(|day| (1..=31).contains(day))?;
+    let month: u64 = MONTHS.iter().position(|name| name == month)? as u64 + 1;
+    let year: u64 = year.parse().ok().filter(|year| *year >= 1970)?;
+    let mut hms = time.split(':').map(|part| if part.len() == 2 { part.parse::<u64>().ok() } else { None });
+    let (hour, min, sec): (u64, u64, u64) = (hms.next()??, hms.next()??, hms.next()??);
+    if hms.next().is_some() || hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+
+    // Convert the civil date to days (see <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>)
+    let year: u64 = if month <= 2 { year - 1 } else { year };
+    let (era, yoe): (u64, u64) = (year / 400, year % 400);
+    let doy: u64 = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe: u64 = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days: u64 = (era * 146097 + doe).checked_sub(719468)?;
+    Some(days * 86400 + hour * 3600 + min * 60 + sec)
+}
+
+/// Checks whether an `If-Match`- or `If-None-Match`-header matches the given entity tag.
+///
+/// # Arguments
+/// - `value`: The value of the header, which is either `*` or a list of entity tags.
+/// - `etag`: The (strong) entity tag of the file, including its quotes.
+/// - `weak`: Whether to use the weak comparison (ignoring `W/`-prefixes) instead of the strong one.
+///
+/// # Returns
+/// True if any of the tags in the header matches, false otherwise.
+fn etag_matches(value: &HeaderValue, etag: &str, weak: bool) -> bool {
+    let Ok(value) = value.to_str() else { return false };
+    if value.trim() == "*" {
+        return true;
+    }
+    value.split(',').map(str::trim).any(|tag| match tag.strip_prefix("W/") {
+        Some(tag) => weak && tag == etag,
+        None => tag == etag,
+    })
+}
+
+
+/// Describes the outcome of evaluating the preconditions of a request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Precondition {
+    /// The preconditions hold (or there are none), so the request should be served normally.
+    Proceed,
+    /// The file wasn't changed since the client last got it, so 304 NOT MODIFIED should be sent.
+    NotModified,
+    /// A precondition failed, so 412 PRECONDITION FAILED should be sent.
+    Failed,
+}
+
+/// Evaluates the conditional headers of a request (RFC 9110, § 13.2.2) against the validators of a file.
+///
+/// `If-Unmodified-Since` is only considered without `If-Match`, and `If-Modified-Since` only without `If-None-Match`.
+///
+/// # Arguments
+/// - `req_headers`: The headers of the request.
+/// - `etag`: The entity tag of the file.
+/// - `modified`: The number of seconds since the UNIX epoch at which the file was last modified, if known.
+///
+/// # Returns
+/// The [`Precondition`] describing how to respond.
+fn evaluate_preconditions(req_headers: &HeaderMap, etag: &str, modified: Option<u64>) -> Precondition {
+    let date = |name: HeaderName| req_headers.get(name).and_then(|value| value.to_str().ok()).and_then(parse_http_date);
+
+    // Step 1 & 2: The client only wants the file if it's (still) the version it knows
+    if let Some(value) = req_headers.get(header::IF_MATCH) {
+        if !etag_matches(value, etag, false) {
+            return Precondition::Failed;
+        }
+    } else if let (Some(since), Some(modified)) = (date(header::IF_UNMODIFIED_SINCE), modified) {
+        if modified > since {
+            return Precondition::Failed;
+        }
+    }
+
+    // Step 3 & 4: The client only wants the file if it's not the version it has cached
+    if let Some(value) = req_headers.get(header::IF_NONE_MATCH) {
+        if etag_matches(value, etag, true) {
+            return Precondition::NotModified;
+        }
+    } else if let (Some(since), Some(modified)) = (date(header::IF_MODIFIED_SINCE), modified) {
+        if modified <= since {
+            return Precondition::NotModified;
+        }
+    }
+    Precondition::Proceed
+}
+
+/// Checks whether an `If-Range`-header matches the validators of a file, i.e., whether the requested ranges should be sent.
+///
+/// # Arguments
+/// - `value`: The value of the `If-Range`-header, which is either an entity tag or an HTTP-date.
+/// - `etag`: The entity tag of the file.
+/// - `modified`: The number of seconds since the UNIX epoch at which the file was last modified, if known.
+///
+/// # Returns
+/// True if the ranges should be sent, or false if the full file should be sent instead.
+fn if_range_matches(value: &HeaderValue, etag: &str, modified: Option<u64>) -> bool {
+    let Ok(value) = value.to_str() else { return false };
+    let value: &str = value.trim();
+    if value.starts_with('"') {
+        value == etag
+    } else {
+        // Weak tags never match, and neither do dates that aren't an exact match
+        modified.is_some_and(|modified| parse_http_date(value) == Some(modified))
+    }
+}
+
+
+/// Describes the outcome of parsing a `Range`-header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Ranges {
+    /// The header is malformed or uses a unit other than bytes, and should be ignored.
+    Ignore,
+    /// None of the ranges can be satisfied, or they overlap.
+    Unsatisfiable,
+    /// The given, sorted list of inclusive `(start, end)` byte ranges should be sent.
+    Satisfiable(Vec<(u64, u64)>),
+}
+
+/// Parses a `Range`-header (RFC 9110, § 14.2) for a file of the given length.
+///
+/// Malformed headers (e.g., `bytes=`, `bytes=+1-2` or `bytes=3-1`) are ignored, such that the whole file is sent, as are headers with more
+/// than [`MAX_RANGES`] ranges. Well-formed ranges that don't overlap the file (e.g., `bytes=-0`, or any range of an empty file) are
+/// unsatisfiable. Ranges that are adjacent or at most [`RANGE_MERGE_GAP`] bytes apart are merged into one.
+///
+/// # Arguments
+/// - `value`: The value of the `Range`-header.
+/// - `len`: The total length of the file the ranges refer to.
+///
+/// # Returns
+/// The [`Ranges`] that should be sent.
+fn parse_ranges(value: &HeaderValue, len: u64) -> Ranges {
+    /// Parses a position in a range, which (unlike [`str::parse()`]) only accepts digits.
+    fn parse_pos(pos: &str) -> Option<u64> {
+        // NOTE: Positions beyond `u64::MAX` are beyond any file, so they saturate instead of failing
+        if pos.is_empty() || !pos.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some(pos.parse::<u64>().unwrap_or(u64::MAX))
+    }
+
+    let Some(specs) = value.to_str().ok().and_then(|value| value.trim().strip_prefix("bytes=")) else { return Ranges::Ignore };
+    if specs.split(',').filter(|spec| !spec.trim().is_empty()).count() > MAX_RANGES {
+        return Ranges::Ignore;
+    }
+
+    // Resolve every spec to actual byte positions
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    let mut any: bool = false;
+    for spec in specs.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+        any = true;
+        let Some((start, end)) = spec.split_once('-') else { return Ranges::Ignore };
+        let (start, end): (&str, &str) = (start.trim(), end.trim());
+        if start.is_empty() {
+            // Suffix range (the last N bytes)
+            let Some(suffix) = parse_pos(end) else { return Ranges::Ignore };
+            if suffix > 0 && len > 0 {
+                ranges.push((len.saturating_sub(suffix), len - 1));
+            }
+        } else {
+            let Some(start) = parse_pos(start) else { return Ranges::Ignore };
+            let end: u64 = if end.is_empty() {
+                u64::MAX
+            } else {
+                match parse_pos(end) {
+                    Some(end) if end >= start => end,
+                    _ => return Ranges::Ignore,
+                }
+            };
+            if start < len {
+                ranges.push((start, end.min(len - 1)));
+            }
+        }
+    }
+    if !any {
+        return Ranges::Ignore;
+    }
+    if ranges.is_empty() {
+        return Ranges::Unsatisfiable;
+    }
+
+    // Refuse overlapping ranges
+    ranges.sort_unstable();
+    if ranges.windows(2).any(|w| w[1].0 <= w[0].1) {
+        return Ranges::Unsatisfiable;
+    }
+
+    // Merge ranges that are close together, as sending the bytes between them is cheaper than another part
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start - last.1 - 1 <= RANGE_MERGE_GAP => last.1 = end,
+            _ => merged.push((start, end)),
+        }
+    }
+    Ranges::Satisfiable(merged)
+}
+
+/// Builds a `multipart/byteranges` body over the given file.
+///
+/// All parts are read through the given handle, which is seeked to every part in turn while the body is streamed.
+///
+/// # Arguments
+/// - `handle`: The opened file to send parts of.
+/// - `path`: The path of that file, for logging.
+/// - `mime_type`: The content type of the file, which is repeated in every part.
+/// - `ranges`: The sorted, non-overlapping, inclusive `(start, end)` byte ranges to send.
+/// - `len`: The total length of the file.
+/// - `permit`: The read permit (if any) to hold on to while the body is being read.
+/// - `limit`: The maximum number of bytes per second to stream, if any.
+///
+/// # Returns
+/// A tuple of the boundary used, the total length of the body and the body itself. The body ends early if the file cannot be read.
+fn multipart_body(
+    mut handle: File,
+    path: &Path,
+    mime_type: &HeaderValue,
+    ranges: &[(u64, u64)],
+    len: u64,
+    permit: Option<OwnedSemaphorePermit>,
+    limit: Option<u64>,
+) -> (String, u64, Body) {
+    // NOTE: The boundary only needs to not occur in the body, which is astronomically unlikely for this one
+    let nanos: u128 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let boundary: String = format!("static-website-host-{nanos:032x}");
+    let mime_type: &str = mime_type.to_str().unwrap_or("application/octet-stream");
+
+    // Render the head of every part up front, as the length of the body has to be known before it's sent
+    let parts: Vec<(String, u64, u64)> = ranges
+        .iter()
+        .map(|(start, end)| {
+            let head: String = format!("\r\n--{boundary}\r\nContent-Type: {mime_type}\r\nContent-Range: bytes {start}-{end}/{len}\r\n\r\n");
+            (head, *start, *end)
+        })
+        .collect();
+    let tail: String = format!("\r\n--{boundary}--\r\n");
+    let body_len: u64 = parts.iter().map(|(head, start, end)| head.len() as u64 + (end - start + 1)).sum::<u64>() + tail.len() as u64;
+
+    // Write the parts into a pipe that is streamed to the client
+    let (mut writer, reader): (DuplexStream, DuplexStream) = tokio::io::duplex(THROTTLE_CHUNK);
+    let path: PathBuf = path.into();
+    tokio::spawn(async move {
+        for (head, start, end) in parts {
+            if writer.write_all(head.as_bytes()).await.is_err() {
+                return;
+            }
+            if let Err(err) = handle.seek(SeekFrom::Start(start)).await {
+                error!(target: WWW, "{}", trace!(("Failed to seek in file '{}'", path.display()), err));
+                return;
+            }
+            // NOTE: Either the client went away or the file shrunk if less was copied; both end the body early
+            match tokio::io::copy(&mut (&mut handle).take(end - start + 1), &mut writer).await {
+                Ok(n) if n == end - start + 1 => {},
+                _ => return,
+            }
+        }
+        let _ = writer.write_all(tail.as_bytes()).await;
+    });
+    (boundary, body_len, stream_body(reader, permit, limit))
+}
+
 /// Streams the given file back to the user.
 ///
+/// If ranges are enabled and the user requested any, only those ranges are sent (for 200 OK responses only). Such responses also carry
+/// `ETag`- and `Last-Modified`-validators, against which conditional requests are evaluated. Otherwise, files of at most
+/// `small_file_threshold` bytes are read into memory and sent as a single body, as are textual files that are transformed (e.g., by
+/// `normalize_line_endings`) or injected with a CSP nonce or the live-reload script. The latter never honour ranges, which they announce
+/// with `Accept-Ranges: none`.
+///
 /// # Arguments
 /// - `state`: A shared [`Context`] that situates this path.
+/// - `req_headers`: The headers of the request that we're responding to.
 /// - `code`: The code to return when the streaming is a success (so far).
 /// - `path`: The full path of the file to stream back.
 ///
 /// # Returns
 /// Either:
 /// - 200 OK with the found file if the the user had access;
+/// - 206 PARTIAL CONTENT with the requested range(s) of the found file;
+/// - 304 NOT MODIFIED if the client already has the current version of the file;
+/// - 412 PRECONDITION FAILED if the client's `If-Match` or `If-Unmodified-Since` doesn't hold;
+/// - 416 RANGE NOT SATISFIABLE if the requested ranges are outside of the file or overlap; or
 /// - 501 INTERNAL SERVER ERROR if something went wrong while streaming the file.
-async fn return_file(state: &Arc<Context>, code: StatusCode, path: impl AsRef<Path>) -> (StatusCode, HeaderMap, AsyncReadBody) {
+async fn return_file(
+    state: &Arc<Context>,
+    req_headers: &HeaderMap,
+    code: StatusCode,
+    path: impl AsRef<Path>,
+) -> (StatusCode, HeaderMap, Body) {
     let path: &Path = path.as_ref();
-    debug!("Returning file '{}' with {} {} to user", path.display(), code.as_u16(), code.canonical_reason().unwrap_or("???"));
+    debug!(target: WWW, "Returning file '{}' with {} {} to user", path.display(), code.as_u16(), code.canonical_reason().unwrap_or("???"));
+
+    // Wait until we may read
+    let permit: Option<OwnedSemaphorePermit> = match &state.read_limiter {
+        Some(limiter) => Some(limiter.acquire().await),
+        None => None,
+    };
 
     // Attempt to open the file
-    let handle: File = match File::open(path).await {
+    let mut handle: File = match with_fs_retries(state, || File::open(path)).await {
         Ok(handle) => handle,
+        // Running out of file descriptors is (hopefully) temporary
+        Err(err) if matches!(err.raw_os_error(), Some(EMFILE | ENFILE)) => {
+            error!(target: WWW, "{}", trace!(("Failed to open file '{}'", path.display()), err));
+            return return_unavailable(state);
+        },
         Err(err) => {
-            error!("{}", trace!(("Failed to open file '{}'", path.display()), err));
-            return (code, HeaderMap::new(), AsyncReadBody::new(b"Internal server error".as_slice()));
+            error!(target: WWW, "{}", trace!(("Failed to open file '{}'", path.display()), err));
+            return (code, HeaderMap::new(), Body::from("Internal server error"));
         },
     };
 
-    // Guess the file's mime type
-    let mime_type: HeaderValue = match path.extension().and_then(OsStr::to_str) {
-        Some("html") => HeaderValue::from_static("text/html"),
-        Some("js") => HeaderValue::from_static("text/javascript"),
-        Some("css") => HeaderValue::from_static("text/css"),
-        _ => HeaderValue::from_static("text/plain"),
+    // Find the bandwidth limit of the file, if any
+    let limit: Option<u64> = path.strip_prefix(&state.site).ok().and_then(|rel| state.bandwidth_matcher.find(rel)).copied();
+
+    // Guess the file's mime type, falling back to its first bytes for unknown extensions if told to do so
+    let ext: Option<&str> = path.extension().and_then(OsStr::to_str);
+    let mime: &str = match lookup_mime(state, ext) {
+        Some(mime) => mime,
+        None if state.sniff_content => {
+            let mut head: Vec<u8> = Vec::with_capacity(CONTENT_SNIFF_LEN);
+            let res: std::io::Result<usize> = (&mut handle).take(CONTENT_SNIFF_LEN as u64).read_to_end(&mut head).await;
+            if let Err(err) = handle.seek(SeekFrom::Start(0)).await {
+                error!(target: WWW, "{}", trace!(("Failed to seek in file '{}'", path.display()), err));
+                return return_error(state, StatusCode::INTERNAL_SERVER_ERROR);
+            }
+            match res {
+                Ok(_) => sniff_content(&head).unwrap_or(DEFAULT_MIME_TYPE),
+                Err(err) => {
+                    warn!(target: WWW, "{}", trace!(("Failed to sniff content of file '{}'", path.display()), err));
+                    DEFAULT_MIME_TYPE
+                },
+            }
+        },
+        None => DEFAULT_MIME_TYPE,
+    };
+    // Textual types get a charset, binary ones never do
+    // NOTE: The MIME types and charsets are validated when loading the config, so these should never fail
+    let overridden: Option<&String> = ext.and_then(|ext| state.charset_overrides.get(ext));
+    let sniffed: Option<String> = if state.sniff_charset && is_textual(mime) && overridden.is_none() {
+        let mut head: Vec<u8> = Vec::with_capacity(SNIFF_LEN);
+        let res: std::io::Result<usize> = (&mut handle).take(SNIFF_LEN as u64).read_to_end(&mut head).await;
+        if let Err(err) = handle.seek(SeekFrom::Start(0)).await {
+            error!(target: WWW, "{}", trace!(("Failed to seek in file '{}'", path.display()), err));
+            return return_error(state, StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        match res {
+            Ok(_) => sniff_charset(&head),
+            Err(err) => {
+                warn!(target: WWW, "{}", trace!(("Failed to sniff charset of file '{}'", path.display()), err));
+                None
+            },
+        }
+    } else {
+        None
     };
+    let charset: Option<&str> =
+        if is_textual(mime) { Some(overridden.map(String::as_str).or(sniffed.as_deref()).unwrap_or(&state.charset)) } else { None };
+    let mime_type: HeaderValue = match charset {
+        Some(charset) => HeaderValue::from_str(&format!("{mime}; charset={charset}")),
+        None => HeaderValue::from_str(mime),
+    }
+    .unwrap_or_else(|_| HeaderValue::from_static(DEFAULT_MIME_TYPE));
 
-    // Get the file's metadata (length, to be precise)
-    let len: u64 = match handle.metadata().await {
-        Ok(md) => md.len(),
+    // Get the file's metadata (length and modification time, to be precise)
+    let (len, modified): (u64, Option<Duration>) = match handle.metadata().await {
+        Ok(md) => (md.len(), md.modified().ok().and_then(|time| time.duration_since(UNIX_EPOCH).ok())),
         Err(err) => {
-            error!("{}", trace!(("Failed to read metadata of file '{}'", path.display()), err));
-            return (code, HeaderMap::new(), AsyncReadBody::new(b"Internal server error".as_slice()));
+            error!(target: WWW, "{}", trace!(("Failed to read metadata of file '{}'", path.display()), err));
+            return (code, HeaderMap::new(), Body::from("Internal server error"));
         },
     };
 
     // Create the header map
     let mut headers: HeaderMap = HeaderMap::new();
-    headers.insert(header::CONTENT_TYPE, mime_type);
+    headers.insert(header::CONTENT_TYPE, mime_type.clone());
     headers.insert(header::CONTENT_LENGTH, HeaderValue::from(len));
-    headers.insert(header::SERVER, HeaderValue::from_str(&format!("{}/{}", state.name, state.version)).unwrap());
+    if let Some(server) = &state.server_header {
+        headers.insert(header::SERVER, server.clone());
+    }
+    // Fingerprinted assets never change, so they may be cached forever
+    if code == StatusCode::OK {
+        if let (Some(fingerprint), Some(name)) = (&state.fingerprint, path.file_name().and_then(OsStr::to_str)) {
+            if fingerprint.is_match(name) {
+                headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("public, max-age=31536000, immutable"));
+            }
+        }
+    }
+    // HTML pages may tell browsers which assets to fetch early
+    if code == StatusCode::OK && mime == "text/html" {
+        if let Some(link) = path.strip_prefix(&state.site).ok().and_then(|rel| state.preload_matcher.find(rel)) {
+            headers.insert(header::LINK, link.clone());
+        }
+    }
+
+    // Textual files may be transformed, as long as they're small enough to do so in memory
+    // NOTE: Transforming them byte-by-byte is only sound for ASCII-compatible charsets
+    let ascii_compatible: bool =
+        charset.map(str::to_ascii_lowercase).is_some_and(|charset| !charset.starts_with("utf-16") && !charset.starts_with("utf-32"));
+    let rewritable: bool = len <= state.transform_max_size && ascii_compatible;
+    // NOTE: Say why textual files are left alone, as that is easily mistaken for a bug
+    let unrewritable_reason: Option<String> = match charset {
+        Some(_) if len > state.transform_max_size => {
+            Some(format!("it is larger than `transform_max_size` ({len} > {} bytes)", state.transform_max_size))
+        },
+        Some(charset) if !ascii_compatible => Some(format!("its charset '{charset}' is not ASCII-compatible")),
+        _ => None,
+    };
+    let transform: bool = (state.normalize_line_endings.is_some() || state.strip_bom) && rewritable;
+    if let Some(reason) = &unrewritable_reason {
+        if state.normalize_line_endings.is_some() || state.strip_bom {
+            debug!(target: WWW, "Not transforming textual file '{}' because {reason}", path.display());
+        }
+        if state.livereload.is_some() && mime == "text/html" {
+            debug!(target: WWW, "Not injecting the live-reload script into HTML file '{}' because {reason}", path.display());
+        }
+    }
+    // HTML files get the live-reload script, if it's enabled
+    let reload_script: Option<String> = match &state.livereload {
+        Some(reload) if mime == "text/html" && rewritable => {
+            Some(livereload::script(&reload.path, state.csp_nonce.as_ref().map(|csp| csp.placeholder.as_str())))
+        },
+        _ => None,
+    };
+    // HTML files get a fresh nonce for their `Content-Security-Policy`, if told to do so
+    // NOTE: Files that cannot be rewritten still get the policy, such that their scripts are blocked instead of the policy disappearing
+    let nonce: Option<(&CspNonce, String)> = match &state.csp_nonce {
+        Some(csp) if mime == "text/html" => match generate_nonce() {
+            Ok(nonce) => Some((csp, nonce)),
+            Err(err) => {
+                error!(target: WWW, "{}", trace!(("Failed to generate CSP nonce"), err));
+                return return_error(state, StatusCode::INTERNAL_SERVER_ERROR);
+            },
+        },
+        _ => None,
+    };
+    if let Some((csp, nonce)) = &nonce {
+        // NOTE: The policy is validated when loading the config, so this should never fail
+        if let Ok(value) = HeaderValue::from_str(&csp.policy.replace(&csp.placeholder, nonce)) {
+            headers.insert(header::CONTENT_SECURITY_POLICY, value);
+        }
+        // Every response is different, so it may never be reused
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        if !rewritable {
+            warn!(
+                target: WWW,
+                "Not replacing CSP nonce placeholders in HTML file '{}' because {}; its scripts will be blocked",
+                path.display(),
+                unrewritable_reason.as_deref().unwrap_or("it cannot be rewritten")
+            );
+        }
+    }
+    let rewrite_nonce: bool = nonce.is_some() && rewritable;
+
+    // Send validators for the file, and see if the client's conditions on them hold
+    // NOTE: Not found pages are not the resource that was asked for, so they never get any; neither do responses with a nonce, which change
+    // every time
+    let modified_secs: Option<u64> = modified.map(|modified| modified.as_secs());
+    let etag: String = match state.etag {
+        EtagStrategy::ContentHash if code == StatusCode::OK && nonce.is_none() => {
+            // Hashing blocks, so do it on a thread that may
+            let (cache, file): (Arc<SriCache>, PathBuf) = (state.etag_cache.clone(), path.into());
+            match tokio::task::spawn_blocking(move || cache.hash(&file)).await {
+                Ok(Ok(hash)) => format!("\"{}\"", hash.trim_start_matches("sha384-")),
+                Ok(Err(err)) => {
+                    warn!(target: WWW, "{}", trace!(("Failed to hash file '{}' for its ETag", path.display()), err));
+                    mtime_size_etag(len, modified)
+                },
+                Err(err) => {
+                    error!(target: WWW, "{}", trace!(("Failed to hash file '{}' for its ETag", path.display()), err));
+                    mtime_size_etag(len, modified)
+                },
+            }
+        },
+        _ => mtime_size_etag(len, modified),
+    };
+    if code == StatusCode::OK && nonce.is_none() {
+        if let Ok(value) = HeaderValue::from_str(&etag) {
+            headers.insert(header::ETAG, value);
+        }
+        if let Some(value) = modified_secs.and_then(|modified| HeaderValue::from_str(&http_date(modified)).ok()) {
+            headers.insert(header::LAST_MODIFIED, value);
+        }
+        match evaluate_preconditions(req_headers, &etag, modified_secs) {
+            Precondition::Proceed => {},
+            Precondition::NotModified => {
+                debug!(target: WWW, "[304] File '{}' was not modified", path.display());
+                headers.remove(header::CONTENT_TYPE);
+                headers.remove(header::CONTENT_LENGTH);
+                return (StatusCode::NOT_MODIFIED, headers, Body::empty());
+            },
+            Precondition::Failed => {
+                debug!(target: WWW, "[412] Preconditions for file '{}' failed", path.display());
+                return return_error(state, StatusCode::PRECONDITION_FAILED);
+            },
+        }
+    }
+
+    // See if we need to send only part(s) of it
+    // NOTE: Ranges refer to the file on disk, and so cannot be sent for transformed files
+    if code == StatusCode::OK && state.ranges && !transform && reload_script.is_none() && nonce.is_none() {
+        headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        let range: Option<&HeaderValue> = match req_headers.get(header::IF_RANGE) {
+            Some(value) if !if_range_matches(value, &etag, modified_secs) => None,
+            _ => req_headers.get(header::RANGE),
+        };
+        match range.map(|range| parse_ranges(range, len)) {
+            None | Some(Ranges::Ignore) => {},
+            Some(Ranges::Unsatisfiable) => {
+                debug!(target: WWW, "[416] Requested ranges for file '{}' cannot be satisfied", path.display());
+                let (code, mut headers, body) = return_error(state, StatusCode::RANGE_NOT_SATISFIABLE);
+                if let Ok(value) = HeaderValue::from_str(&format!("bytes */{len}")) {
+                    headers.insert(header::CONTENT_RANGE, value);
+                }
+                return (code, headers, body);
+            },
+            Some(Ranges::Satisfiable(ranges)) if ranges.len() == 1 => {
+                let (start, end): (u64, u64) = ranges[0];
+                debug!(target: WWW, "Sending range {start}-{end} of file '{}'", path.display());
+                if let Err(err) = handle.seek(SeekFrom::Start(start)).await {
+                    error!(target: WWW, "{}", trace!(("Failed to seek in file '{}'", path.display()), err));
+                    return return_error(state, StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                headers.insert(header::CONTENT_LENGTH, HeaderValue::from(end - start + 1));
+                if let Ok(value) = HeaderValue::from_str(&format!("bytes {start}-{end}/{len}")) {
+                    headers.insert(header::CONTENT_RANGE, value);
+                }
+                let body: Body = stream_body(handle.take(end - start + 1), permit, limit);
+                return (StatusCode::PARTIAL_CONTENT, headers, body);
+            },
+            Some(Ranges::Satisfiable(ranges)) => {
+                debug!(target: WWW, "Sending {} ranges of file '{}'", ranges.len(), path.display());
+                let (boundary, body_len, body): (String, u64, Body) = multipart_body(handle, path, &mime_type, &ranges, len, permit, limit);
+                if let Ok(value) = HeaderValue::from_str(&format!("multipart/byteranges; boundary={boundary}")) {
+                    headers.insert(header::CONTENT_TYPE, value);
+                }
+                headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body_len));
+                return (StatusCode::PARTIAL_CONTENT, headers, body);
+            },
+        }
+    } else if code == StatusCode::OK && state.ranges {
+        // Tell clients not to bother, instead of leaving them to find out the hard way
+        headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("none"));
+    }
+
+    // Small (and transformed) files are sent in one go, which saves many small socket writes
+    if transform || reload_script.is_some() || rewrite_nonce || (len > 0 && len <= state.small_file_threshold) {
+        let mut buf: Vec<u8> = Vec::with_capacity(len as usize);
+        // NOTE: Never read more than we announced, in case the file grew in the meantime
+        if let Err(err) = handle.take(len).read_to_end(&mut buf).await {
+            error!(target: WWW, "{}", trace!(("Failed to read file '{}'", path.display()), err));
+            return return_error(state, StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        // NOTE: Files with NUL-bytes are binary after all, regardless of their extension
+        if transform && !buf.contains(&0) {
+            buf = transform_text(&buf, state.normalize_line_endings, state.strip_bom);
+        }
+        if let (Some(script), false) = (&reload_script, buf.contains(&0)) {
+            buf = insert_before_body_end(&buf, script.as_bytes());
+        }
+        if let (Some((csp, nonce)), true) = (&nonce, rewrite_nonce) {
+            buf = replace_bytes(&buf, csp.placeholder.as_bytes(), nonce.as_bytes());
+        }
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(buf.len()));
+        let body: Body = match limit {
+            Some(_) => stream_body(Cursor::new(buf), permit, limit),
+            None => Body::from(Bytes::from(buf)),
+        };
+        return (code, headers, body);
+    }
+
+    // Otherwise, stream it as the body
+    let body: Body = stream_body(handle, permit, limit);
+    (code, headers, body)
+}
+
+
+
+/// Returns the effective mapping of file extensions to MIME types.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that may override the built-in [`MIME_TYPES`].
+///
+/// # Returns
+/// The built-in [`MIME_TYPES`] merged with the configured `mime_types`, sorted by extension. Files with any other extension are served as
+/// [`DEFAULT_MIME_TYPE`].
+pub fn mime_types(state: &Context) -> BTreeMap<&str, &str> {
+    let mut types: BTreeMap<&str, &str> = MIME_TYPES.iter().copied().collect();
+    types.extend(state.mime_types.iter().map(|(ext, mime)| (ext.as_str(), mime.as_str())));
+    types
+}
+
+/// Returns the methods that site files may be requested with.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+///
+/// # Returns
+/// GET, HEAD, any methods aliased for GET and OPTIONS.
+pub(crate) fn allowed_methods(state: &Context) -> Vec<&str> {
+    ["GET", "HEAD"].into_iter().chain(state.get_alias_methods.iter().map(String::as_str)).chain(["OPTIONS"]).collect()
+}
+
+/// Logs why a file was not found, at the configured `not_found_log_level`.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `reason`: The message to log.
+#[inline]
+fn log_not_found(state: &Context, reason: Arguments) {
+    if let Some(level) = state.not_found_log_level.level() {
+        log!(target: WWW, level, "{reason}");
+    }
+}
+
+/// Returns a plain error response with the given status code to the user.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `code`: The (error) status code to return.
+///
+/// # Returns
+/// The given `code` with its canonical reason as body.
+pub(crate) fn return_error(state: &Arc<Context>, code: StatusCode) -> (StatusCode, HeaderMap, Body) {
+    debug!(target: WWW, "Returning {} {} to user", code.as_u16(), code.canonical_reason().unwrap_or("???"));
+    let reason: &'static str = code.canonical_reason().unwrap_or("Error");
 
-    // Stream it as the body
-    let body: AsyncReadBody = AsyncReadBody::new(handle);
+    // Create the header map
+    let mut headers: HeaderMap = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(reason.len()));
+    if let Some(server) = &state.server_header {
+        headers.insert(header::SERVER, server.clone());
+    }
+    (code, headers, Body::from(reason))
+}
+
+/// Returns a redirect response to the user.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `code`: The (redirection) status code to return, e.g., 301 MOVED PERMANENTLY.
+/// - `location`: The target to redirect to.
+///
+/// # Returns
+/// The given `code` with a `Location`-header set to `location`, or 500 INTERNAL SERVER ERROR if it isn't a valid header value.
+pub(crate) fn return_redirect(state: &Arc<Context>, code: StatusCode, location: &str) -> (StatusCode, HeaderMap, Body) {
+    let Ok(location) = HeaderValue::from_str(location) else { return return_error(state, StatusCode::INTERNAL_SERVER_ERROR) };
+    let (code, mut headers, body): (StatusCode, HeaderMap, Body) = return_error(state, code);
+    headers.insert(header::LOCATION, location);
     (code, headers, body)
 }
 
+/// Returns a 503 SERVICE UNAVAILABLE response to the user, telling them to try again later.
+///
+/// This should be used by every path that is temporarily unable to serve files, such that they all respond consistently.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+///
+/// # Returns
+/// 503 SERVICE UNAVAILABLE with a `Retry-After`-header set to the configured number of seconds.
+pub(crate) fn return_unavailable(state: &Arc<Context>) -> (StatusCode, HeaderMap, Body) {
+    let (code, mut headers, body): (StatusCode, HeaderMap, Body) = return_error(state, StatusCode::SERVICE_UNAVAILABLE);
+    headers.insert(header::RETRY_AFTER, HeaderValue::from(state.retry_after_seconds));
+    (code, headers, body)
+}
 
+/// Streams the not found file back to the user, with its configured status code and headers.
+///
+/// The not found file is streamed as-is instead of being resolved like a request path. As such, this never re-enters not found resolution,
+/// even if the not found file itself is missing or was what the user requested.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `req_headers`: The headers of the request that we're responding to.
+/// - `path`: The (normalized) request path that wasn't found, which selects any of the `not_found_overrides`.
+///
+/// # Returns
+/// The not found file with the configured status code (404 NOT FOUND by default) and any configured additional headers.
+async fn return_not_found(state: &Arc<Context>, req_headers: &HeaderMap, path: &Path) -> (StatusCode, HeaderMap, Body) {
+    let code: StatusCode = StatusCode::from_u16(state.not_found_status).unwrap_or(StatusCode::NOT_FOUND);
+    let (code, mut headers, body): (StatusCode, HeaderMap, Body) = match (state.not_found_matcher.find(path), &state.not_found_file) {
+        (Some(path), _) => return_file(state, req_headers, code, path).await,
+        (None, NotFoundFile::Single(path)) => return_file(state, req_headers, code, path).await,
+        (None, NotFoundFile::ByType(files)) => {
+            let accept: Option<&str> = req_headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+            let (mime, path): (&str, &Path) = negotiate_not_found(files, accept);
+            let (code, mut headers, body): (StatusCode, HeaderMap, Body) = return_file(state, req_headers, code, path).await;
+            // Send it as the type it was selected for, regardless of its extension
+            // NOTE: If the configured type is not a valid header value, we just keep the guessed one
+            let mime_type: String = if is_textual(mime) { format!("{mime}; charset={}", state.charset) } else { mime.into() };
+            if let Ok(mime_type) = HeaderValue::from_str(&mime_type) {
+                headers.insert(header::CONTENT_TYPE, mime_type);
+            }
+            VaryBuilder::new().add("Accept").apply(&mut headers);
+            (code, headers, body)
+        },
+    };
+    for (name, value) in &state.not_found_header_map {
+        headers.insert(name, value.clone());
+    }
+    (code, headers, body)
+}
 
 
 
 /***** LIBRARY *****/
+/// Collects the request headers that affected a response (e.g., by negotiation), so they can be sent as a single `Vary`-header.
+///
+/// Features that negotiate should add their dimension to the same builder instead of setting `Vary` themselves, so they don't overwrite
+/// each other.
+#[derive(Clone, Debug, Default)]
+pub struct VaryBuilder {
+    /// The names of the request headers, in the order they were added.
+    dims: Vec<String>,
+}
+impl VaryBuilder {
+    /// Creates a builder without any dimensions.
+    ///
+    /// # Returns
+    /// A new VaryBuilder.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Adds a request header that affected the response. Adding the same one twice (case-insensitively) does nothing.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the request header.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    pub fn add(&mut self, name: &str) -> &mut Self {
+        let name: &str = name.trim();
+        if !name.is_empty() && !self.dims.iter().any(|dim| dim.eq_ignore_ascii_case(name)) {
+            self.dims.push(name.into());
+        }
+        self
+    }
+
+    /// Checks whether any dimension was added.
+    ///
+    /// # Returns
+    /// True if no request header affected the response, false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.dims.is_empty() }
+
+    /// Writes the collected dimensions to the given response headers.
+    ///
+    /// Any `Vary`-headers that are already in there are merged in rather than overwritten.
+    ///
+    /// # Arguments
+    /// - `headers`: The response headers to update.
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        let mut all: Self = Self::new();
+        for value in headers.get_all(header::VARY).iter().filter_map(|value| value.to_str().ok()) {
+            for name in value.split(',') {
+                all.add(name);
+            }
+        }
+        for dim in &self.dims {
+            all.add(dim);
+        }
+        if all.is_empty() {
+            return;
+        }
+        if let Ok(value) = HeaderValue::from_str(&all.dims.join(", ")) {
+            headers.insert(header::VARY, value);
+        }
+    }
+}
+
+
+
 /// Fetches files according to the given path.
 ///
 /// This respects the user-provided [`SiteSecurity`](crate::state::SiteSecurity)-file, which tells us what kind of security requirements each file has.
 ///
 /// # Arguments
 /// - `state`: A shared [`Context`] that situates this path.
+/// - `headers`: The headers of the incoming request.
+/// - `uri`: The target of the incoming request.
 /// - `path`: The path of the file that was matched.
 ///
 /// # Returns
 /// Either:
-/// - 200 OK with the found file if the the user had access; or
+/// - 200 OK with the found file if the the user had access;
+/// - 301 MOVED PERMANENTLY to the directory if an index file was requested explicitly and `index_redirect` is enabled;
+/// - 400 BAD REQUEST or 414 URI TOO LONG if the path has too many components or is too long, respectively;
+/// - 403 FORBIDDEN if the path requires a signed URL and the request's signature is missing, invalid or expired;
+/// - 410 GONE with the gone page (if any) if the path was removed for good; or
 /// - 404 NOT FOUND with the not-found-page if the file was not found.
 ///
 /// # Errors
 /// This function errors if it found but failed to load a file.
 #[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
-pub async fn handle(State(state): State<Arc<Context>>, path: Option<extract::Path<PathBuf>>) -> (StatusCode, HeaderMap, AsyncReadBody) {
-    let path: PathBuf = path.map(|p| p.0).unwrap_or_default();
-    info!("Handling GET '{}'", path.display());
+pub async fn handle(
+    State(state): State<Arc<Context>>,
+    headers: HeaderMap,
+    uri: Uri,
+    path: Result<extract::Path<PathBuf>, PathRejection>,
+) -> (StatusCode, HeaderMap, Body) {
+    let path: PathBuf = match request_path(path) {
+        Ok(path) => path,
+        Err(code) => return return_error(&state, code),
+    };
+    info!(target: WWW, "Handling GET '{}'", path.display());
+
+    // Simulate a slow origin, if told to do so
+    if let Some(delay) = state.artificial_delay.as_ref().and_then(|delay| artificial_delay(delay, &path)) {
+        debug!(target: WWW, "Delaying response by {}ms on purpose", delay.as_millis());
+        tokio::time::sleep(delay).await;
+    }
+
+    // Refuse pathological paths before touching the filesystem
+    if path.as_os_str().len() > state.max_path_length {
+        debug!(target: WWW, "[414] Request path is longer than {} bytes", state.max_path_length);
+        return return_error(&state, StatusCode::URI_TOO_LONG);
+    }
+    if path.components().count() > state.max_path_components {
+        debug!(target: WWW, "[400] Request path has more than {} components", state.max_path_components);
+        return return_error(&state, StatusCode::BAD_REQUEST);
+    }
+
+    // Redirect moved paths before resolving any files
+    // SAFETY: We can call `unwrap()` because we never panic while holding the lock.
+    let redirect: Option<(StatusCode, String)> = state.redirect_map.read().unwrap().lookup(uri.path(), uri.query());
+    if let Some((code, location)) = redirect {
+        debug!(target: WWW, "[{}] Redirecting '{}' to '{location}'", code.as_u16(), uri.path());
+        return return_redirect(&state, code, &location);
+    }
 
     // First, get the full file path
-    let mut file_path: PathBuf = state.site.clone();
-    file_path.extend(path.components().skip_while(|c| matches!(c, Component::RootDir)));
-
-    // Canonicalize it
-    let mut file_path: PathBuf = match file_path.canonicalize() {
-        // If found, then ensure it didn't escape
-        Ok(path) => {
-            if path.starts_with(&state.site) {
-                path
-            } else {
-                debug!("[404] Target file path '{}' escaped site directory", file_path.display());
-                return return_file(&state, StatusCode::NOT_FOUND, &state.not_found_file).await;
-            }
-        },
-        Err(err) => {
-            debug!("{}", trace!(("[404] Target file path '{}' cannot be canonicalized", file_path.display()), err));
-            return return_file(&state, StatusCode::NOT_FOUND, &state.not_found_file).await;
+    let path: PathBuf = match normalize_path(&path, state.parent_dirs) {
+        Ok(path) => path,
+        Err(StatusCode::NOT_FOUND) => {
+            log_not_found(&state, format_args!("[404] Request path '{}' goes above the site directory", path.display()));
+            return return_not_found(&state, &headers, Path::new("")).await;
         },
+        Err(code) => return return_error(&state, code),
+    };
+    // Content that was removed for good is gone, rather than not found
+    if state.gone_matcher.find(&path).is_some() {
+        debug!(target: WWW, "[410] Request path '{}' was removed", path.display());
+        return match &state.gone_file {
+            Some(gone_file) => return_file(&state, &headers, StatusCode::GONE, gone_file).await,
+            None => return_error(&state, StatusCode::GONE),
+        };
+    }
+    // Some paths may only be accessed through signed URLs
+    if !has_valid_signature(&state, &uri, &path) {
+        return return_error(&state, StatusCode::FORBIDDEN);
+    }
+    // Single-file sites serve one file for everything else
+    if let Some(single_file) = &state.single_file {
+        debug!(target: WWW, "Target file path: {} (single file)", single_file.display());
+        return return_file(&state, &headers, StatusCode::OK, single_file).await;
+    }
+    // Crawlers may be sent a (e.g., prerendered) alternative instead, if told to do so
+    let bot_file: Option<&PathBuf> = if state.bot_user_agents.is_empty() { None } else { state.bot_matcher.find(&path) };
+    if let Some(bot_file) = bot_file {
+        if is_bot(&state, &headers) {
+            info!(target: WWW, "Serving bot file '{}' for request path '{}' to crawler", bot_file.display(), path.display());
+            let (code, mut res_headers, body): (StatusCode, HeaderMap, Body) = return_file(&state, &headers, StatusCode::OK, bot_file).await;
+            VaryBuilder::new().add("User-Agent").apply(&mut res_headers);
+            return (code, res_headers, body);
+        }
+    }
+    // Index files are only reachable through their directory, if told to do so
+    let dir: &Path = path.parent().unwrap_or(Path::new(""));
+    if state.index_redirect
+        && path.file_name().and_then(OsStr::to_str).is_some_and(|name| index_files(&state, dir).iter().any(|index| index == name))
+    {
+        // NOTE: Only redirect to directories that will actually serve this index file; others are served as-is
+        if index_redirects(&state, &path, headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok())) {
+            let location: String = format!("{}{}", dir_location(dir), uri.query().map(|query| format!("?{query}")).unwrap_or_default());
+            debug!(target: WWW, "[301] Redirecting explicit request for index file '{}' to '{location}'", path.display());
+            return return_redirect(&state, StatusCode::MOVED_PERMANENTLY, &location);
+        }
+    }
+    // The exact root may have its own landing page instead of an index file
+    let is_root_file: bool = state.root_file.is_some() && path.as_os_str().is_empty();
+    let path: PathBuf = match &state.root_file {
+        // NOTE: It's resolved when loading the config, but resolving it again needs it relative to the site
+        Some(root_file) if path.as_os_str().is_empty() => root_file.strip_prefix(&state.site).unwrap_or(root_file.as_path()).into(),
+        _ => path,
+    };
+
+    // Try the resolution steps in order until one of them finds a file
+    // NOTE: The root file is always served as-is
+    let steps: &[Resolution] = if is_root_file { &[Resolution::Exact] } else { &state.resolution_order };
+    let mut found: Option<PathBuf> = None;
+    let mut negotiated: Option<Option<String>> = None;
+    let mut resolution: &'static str = "direct";
+    let mut reason: String = format!("[404] Request path '{}' does not resolve to a file", path.display());
+    for step in steps {
+        let candidate: PathBuf = match step {
+            Resolution::Exact | Resolution::DirIndex => path.clone(),
+            Resolution::HtmlSuffix if path.as_os_str().is_empty() => continue,
+            Resolution::HtmlSuffix => {
+                let mut candidate: OsString = path.clone().into_os_string();
+                candidate.push(".html");
+                candidate.into()
+            },
+        };
+        let file_path: PathBuf = state.site.join(&candidate);
+
+        // Canonicalize it (to resolve symlinks)
+        let file_path: PathBuf = match with_fs_retries(&state, || tokio::fs::canonicalize(&file_path)).await {
+            // If found, then ensure it didn't escape
+            Ok(canonical) => {
+                if canonical.starts_with(&state.site) {
+                    canonical
+                } else {
+                    reason = format!("[404] Target file path '{}' escaped site directory", file_path.display());
+                    continue;
+                }
+            },
+            Err(_) if !state.site.is_dir() => {
+                error!(target: WWW, "[503] Site directory '{}' has disappeared", state.site.display());
+                return return_unavailable(&state);
+            },
+            Err(err) => {
+                reason = trace!(("[404] Target file path '{}' cannot be canonicalized", file_path.display()), err).to_string();
+                continue;
+            },
+        };
+        // Refuse paths whose casing differs from what's on disk, if told to do so
+        if state.case_sensitive && !has_exact_case(&state.site, &candidate) {
+            reason = format!("[404] Request path '{}' does not match the casing on disk", candidate.display());
+            continue;
+        }
+
+        match step {
+            Resolution::Exact | Resolution::HtmlSuffix => {
+                if file_path.is_dir() {
+                    continue;
+                }
+                resolution = if *step == Resolution::Exact { "direct" } else { "clean-url" };
+                found = Some(file_path);
+                break;
+            },
+            // If it's a directory, then append its index file (or a language variant of it)
+            Resolution::DirIndex => {
+                if !file_path.is_dir() {
+                    continue;
+                }
+                let variant: Option<(PathBuf, String)> = state.language_negotiation.as_ref().and_then(|negotiation| {
+                    negotiate_index(negotiation, &file_path, headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()))
+                });
+                negotiated = state.language_negotiation.as_ref().map(|_| variant.as_ref().map(|(_, lang)| lang.clone()));
+                let index: PathBuf = match variant {
+                    Some((path, _)) => path,
+                    None => match index_files(&state, &path).iter().map(|name| file_path.join(name)).find(|index| index.is_file()) {
+                        Some(index) => {
+                            // Multiple candidates usually mean leftovers in the build output
+                            if state.warn_on_ambiguous_index {
+                                let others: Vec<&String> = index_files(&state, &path)
+                                    .iter()
+                                    .filter(|name| file_path.join(name) != index && file_path.join(name).is_file())
+                                    .collect();
+                                if !others.is_empty() {
+                                    warn!(
+                                        target: WWW,
+                                        "Directory '{}' has multiple index files; serving '{}' instead of {}",
+                                        file_path.display(),
+                                        index.display(),
+                                        others.iter().map(|name| format!("'{name}'")).collect::<Vec<String>>().join(", ")
+                                    );
+                                }
+                            }
+                            index
+                        },
+                        None => {
+                            reason = format!("[404] Directory '{}' has no index file", file_path.display());
+                            negotiated = None;
+                            continue;
+                        },
+                    },
+                };
+                resolution = "index";
+                found = Some(index);
+                break;
+            },
+        }
+    }
+    let Some(file_path) = found else {
+        log_not_found(&state, format_args!("{reason}"));
+        return return_not_found(&state, &headers, &path).await;
     };
-    // If it's a directory, then append `index.html`
-    if file_path.is_dir() {
-        file_path.push("index.html");
-        if !file_path.exists() {
-            debug!("[404] Target file path '{}' not found", file_path.display());
-            return return_file(&state, StatusCode::NOT_FOUND, &state.not_found_file).await;
+    // Clean URLs and directories with the same name usually mean leftovers in the build output
+    if state.warn_on_clean_url_collision && steps.contains(&Resolution::HtmlSuffix) && steps.contains(&Resolution::DirIndex) {
+        if let Some((suffixed, index)) = clean_url_collision(&state, &path) {
+            warn!(
+                target: WWW,
+                "Request path '{}' resolves to both '{}' and '{}'; serving '{}' (see `resolution_order`)",
+                path.display(),
+                suffixed.display(),
+                index.display(),
+                file_path.display()
+            );
+        }
+    }
+    // Never serve files that are protected (the index file may be a symlink, so resolve it once more)
+    if !state.protected.is_empty() && state.protected.contains(&file_path.canonicalize().unwrap_or_else(|_| file_path.clone())) {
+        if state.blocked_status == 403 {
+            debug!(target: WWW, "[403] Target file path '{}' is protected", file_path.display());
+            return return_error(&state, StatusCode::FORBIDDEN);
+        }
+        log_not_found(&state, format_args!("[404] Target file path '{}' is protected", file_path.display()));
+        return return_not_found(&state, &headers, &path).await;
+    }
+    // Check the signature once more for the file on disk, as the request path may have reached it through a symlink or different casing
+    if !has_valid_signature(&state, &uri, file_path.strip_prefix(&state.site).unwrap_or(&file_path)) {
+        return return_error(&state, StatusCode::FORBIDDEN);
+    }
+    debug!(target: WWW, "Target file path: {}", file_path.display());
+
+    // Empty files are often the result of a broken build
+    if (state.warn_on_empty || state.empty_html_not_found) && file_path.metadata().is_ok_and(|md| md.len() == 0) {
+        if state.empty_html_not_found && guess_mime(&state, file_path.extension().and_then(OsStr::to_str)) == "text/html" {
+            warn!(target: WWW, "[404] Target file path '{}' is an empty HTML file", file_path.display());
+            return return_not_found(&state, &headers, &path).await;
+        }
+        if state.warn_on_empty {
+            warn!(target: WWW, "Serving empty file '{}'", file_path.display());
         }
     }
-    debug!("Target file path: {}", file_path.display());
 
     // OK, return the file!
-    return_file(&state, StatusCode::OK, file_path).await
+    let (code, mut res_headers, body): (StatusCode, HeaderMap, Body) = return_file(&state, &headers, StatusCode::OK, &file_path).await;
+    if state.debug_headers && code.is_success() {
+        // Only reveal the path relative to the site, never where the site lives
+        let served: &Path = file_path.strip_prefix(&state.site).unwrap_or(&file_path);
+        if let Ok(value) = HeaderValue::from_str(&format!("/{}", served.display())) {
+            res_headers.insert(HeaderName::from_static("x-served-file"), value);
+        }
+        res_headers.insert(HeaderName::from_static("x-resolution"), HeaderValue::from_static(resolution));
+    }
+    let mut vary: VaryBuilder = VaryBuilder::new();
+    if bot_file.is_some() {
+        vary.add("User-Agent");
+    }
+    if let Some(lang) = negotiated {
+        vary.add("Accept-Language");
+        if let Some(value) = lang.and_then(|lang| HeaderValue::from_str(&lang).ok()) {
+            res_headers.insert(header::CONTENT_LANGUAGE, value);
+        }
+    }
+    vary.apply(&mut res_headers);
+    (code, res_headers, body)
+}
+
+
+/// Serves ACME HTTP-01 challenge files from the configured challenge directory.
+///
+/// Challenges bypass everything specific to the site (index files, protected files, redirects, ...) and are always sent as `text/plain`.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `headers`: The headers of the incoming request.
+/// - `token`: The token of the challenge that was requested.
+///
+/// # Returns
+/// Either 200 OK with the challenge file, or 404 NOT FOUND with the not-found-page if the token is illegal or unknown.
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn handle_acme_challenge(
+    State(state): State<Arc<Context>>,
+    headers: HeaderMap,
+    extract::Path(token): extract::Path<String>,
+) -> (StatusCode, HeaderMap, Body) {
+    info!(target: WWW, "Handling ACME challenge '{token}'");
+    let Some(acme) = &state.acme_challenge else { return return_not_found(&state, &headers, Path::new("")).await };
+
+    // Tokens are base64url, so this also makes sure we never leave the challenge directory
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')) {
+        log_not_found(&state, format_args!("[404] Illegal ACME challenge token {token:?}"));
+        return return_not_found(&state, &headers, Path::new("")).await;
+    }
+    let path: PathBuf = acme.dir.join(&token);
+    if !path.is_file() {
+        log_not_found(&state, format_args!("[404] ACME challenge file '{}' not found", path.display()));
+        return return_not_found(&state, &headers, Path::new("")).await;
+    }
+
+    // Serve it as-is
+    let (code, mut res_headers, body): (StatusCode, HeaderMap, Body) = return_file(&state, &headers, StatusCode::OK, &path).await;
+    if code.is_success() {
+        res_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+    }
+    (code, res_headers, body)
+}
+
+
+/// Answers `OPTIONS`-requests for paths in the site.
+///
+/// Auxiliary paths (e.g., the `version_path`) are answered by their own routes, so this only ever reports on site files.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+///
+/// # Returns
+/// The configured `options_status` (204 NO CONTENT by default) with an `Allow`-header listing the methods that site files may be
+/// requested with.
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn handle_options(State(state): State<Arc<Context>>) -> (StatusCode, HeaderMap) {
+    debug!(target: WWW, "Handling OPTIONS for site path");
+    let code: StatusCode = StatusCode::from_u16(state.options_status).unwrap_or(StatusCode::NO_CONTENT);
+    let mut headers: HeaderMap = HeaderMap::new();
+    if let Ok(allow) = HeaderValue::from_str(&allowed_methods(&state).join(", ")) {
+        headers.insert(header::ALLOW, allow);
+    }
+    if let Some(server) = &state.server_header {
+        headers.insert(header::SERVER, server.clone());
+    }
+    (code, headers)
+}
+
+/// Fetches files for requests with methods other than GET (or HEAD).
+///
+/// Requests using any of the configured `get_alias_methods` are treated as if they were GET-requests. Anything else is refused.
+///
+/// # Arguments
+/// - `state`: A shared [`Context`] that situates this path.
+/// - `method`: The method of the incoming request.
+/// - `headers`: The headers of the incoming request.
+/// - `uri`: The target of the incoming request.
+/// - `path`: The path of the file that was matched.
+///
+/// # Returns
+/// Either the same as [`handle()`] if the `method` is aliased for GET, or 405 METHOD NOT ALLOWED otherwise.
+#[cfg_attr(feature = "axum-debug", axum_macros::debug_handler)]
+pub async fn handle_alias(
+    State(state): State<Arc<Context>>,
+    method: Method,
+    headers: HeaderMap,
+    uri: Uri,
+    path: Result<extract::Path<PathBuf>, PathRejection>,
+) -> (StatusCode, HeaderMap, Body) {
+    if !state.get_alias_methods.iter().any(|alias| alias == method.as_str()) {
+        debug!(target: WWW, "[405] Method {method} is not allowed");
+        let (code, mut headers, body): (StatusCode, HeaderMap, Body) = return_error(&state, StatusCode::METHOD_NOT_ALLOWED);
+        let allow: String = allowed_methods(&state).join(", ");
+        if let Ok(allow) = HeaderValue::from_str(&allow) {
+            headers.insert(header::ALLOW, allow);
+        }
+        return (code, headers, body);
+    }
+    debug!(target: WWW, "Treating {method} as GET");
+    let (code, mut res_headers, body): (StatusCode, HeaderMap, Body) = handle(State(state.clone()), headers, uri, path).await;
+    if state.debug_headers && res_headers.contains_key("x-resolution") {
+        res_headers.insert(HeaderName::from_static("x-resolution"), HeaderValue::from_static("alias"));
+    }
+    (code, res_headers, body)
 }
@@ -0,0 +1,101 @@
+//  ACCESS.rs
+//    by Lut99
+//
+//  Created:
+//    17 Oct 2026, 00:13:18
+//  Last edited:
+//    17 Oct 2026, 04:37:21
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests what ends up in the access log.
+//
+
+mod common;
+
+use std::sync::{Mutex, Once};
+
+use common::TestServer;
+use log::{LevelFilter, Log, Metadata, Record};
+use static_website_host::logging;
+
+
+/***** HELPERS *****/
+/// The lines logged on the access log target so far, by all tests.
+static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// Makes sure the [`Capture`] logger is installed only once.
+static INSTALL: Once = Once::new();
+
+/// A logger that remembers the lines on the access log target.
+struct Capture;
+impl Log for Capture {
+    #[inline]
+    fn enabled(&self, metadata: &Metadata) -> bool { metadata.target() == logging::ACCESS }
+
+    #[inline]
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            LINES.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    #[inline]
+    fn flush(&self) {}
+}
+
+
+
+/// Installs the [`Capture`] logger, if no test did so before.
+fn capture() {
+    INSTALL.call_once(|| {
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(LevelFilter::Info);
+    });
+}
+
+/// Returns the access log lines so far that contain the given text.
+///
+/// # Arguments
+/// - `needle`: The text to look for, which tells the lines of a test apart from those of others running at the same time.
+///
+/// # Returns
+/// The matching lines, in the order in which they were logged.
+fn lines_with(needle: &str) -> Vec<String> { LINES.lock().unwrap().iter().filter(|line| line.contains(needle)).cloned().collect() }
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn only_allowed_headers_are_logged() {
+    capture();
+    let server: TestServer = common::spawn("access", "access_log:\n  log_headers: [ 'User-Agent' ]\n").await;
+
+    let res: String =
+        server.request("GET", "/index.html?email=me@example.com", "User-Agent: curl/8.5.0\r\nReferer: https://example.com\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+
+    let lines: Vec<String> = lines_with(" /index.html ");
+    assert_eq!(lines.len(), 1, "Expected a single access log line, got {lines:?}");
+    let line: &str = &lines[0];
+    assert!(line.starts_with('#'), "Expected a hashed client IP, got {line:?}");
+    assert!(line.contains(" \"GET /index.html HTTP/1.1\" 200 "), "Expected the request without its query, got {line:?}");
+    assert!(line.ends_with(" user-agent=\"curl/8.5.0\""), "Expected only the allowed header, got {line:?}");
+    assert!(!line.contains("127.0.0.1") && !line.contains("example.com"), "Expected no personal data, got {line:?}");
+}
+
+#[tokio::test]
+async fn version_path_is_not_logged() {
+    capture();
+    let server: TestServer = common::spawn("access-version", "version_path: '/version'\naccess_log: {}\n").await;
+
+    let res: String = server.request("GET", "/version", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    let res: String = server.request("GET", "/versions.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+
+    assert_eq!(lines_with(" /version "), Vec::<String>::new(), "Expected no access log line for the version path");
+    assert_eq!(lines_with(" /versions.html ").len(), 1, "Expected an access log line for other paths");
+}
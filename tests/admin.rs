@@ -0,0 +1,66 @@
+//  ADMIN.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:24:45
+//  Last edited:
+//    17 Oct 2026, 05:45:39
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the read-only admin API.
+//
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestServer;
+use static_website_host::state::Context;
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn connections_require_token() {
+    let server: TestServer = common::spawn("admin-unauthorized", "admin:\n  token: 'secret'\n").await;
+    for headers in ["", "Authorization: Bearer wrong\r\n", "Authorization: Basic c2VjcmV0\r\n"] {
+        let res: String = server.request("GET", "/_admin/connections", headers).await;
+        assert!(res.starts_with("HTTP/1.1 401"), "Expected 401 UNAUTHORIZED for {headers:?}, got {res:?}");
+        assert!(res.to_ascii_lowercase().contains("www-authenticate: bearer\r\n"), "Expected a challenge, got {res:?}");
+    }
+}
+
+#[tokio::test]
+async fn connections_are_listed() {
+    let server: TestServer = common::spawn("admin-connections", "admin:\n  token: 'secret'\n").await;
+    let res: String = server.request("GET", "/_admin/connections", "Authorization: Bearer secret\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(res.contains("\"last_path\":\"/_admin/connections\""), "Expected the requesting connection, got {res:?}");
+    assert!(res.contains("\"requests\":1"), "Expected a single request on the connection, got {res:?}");
+}
+
+#[tokio::test]
+async fn stats_are_summarized() {
+    let server: TestServer = common::spawn("admin-stats", "admin:\n  token: 'secret'\nmax_concurrent_reads: 4\n").await;
+    let res: String = server.request("GET", "/_admin/stats", "Authorization: Bearer secret\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(res.contains("\"active_connections\":1"), "Expected the requesting connection, got {res:?}");
+    assert!(res.contains("\"read_queue_depth\":0"), "Expected an empty read queue, got {res:?}");
+}
+
+#[tokio::test]
+async fn admin_is_disabled_by_default() {
+    let server: TestServer = common::spawn("admin-disabled", "").await;
+    let res: String = server.request("GET", "/_admin/connections", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+}
+
+#[test]
+fn token_is_not_printed() {
+    let (dir, _, state): (PathBuf, PathBuf, Context) = common::load("admin-print-config", "admin:\n  token: 'correct-horse-battery'\n");
+    let config: String = serde_yml::to_string(&state).unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+    assert!(config.contains("<redacted>"), "Expected the token to be redacted, got {config:?}");
+    assert!(!config.contains("correct-horse-battery"), "Expected no token in the printed config, got {config:?}");
+}
@@ -0,0 +1,43 @@
+//  BANDWIDTH.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 11:11:09
+//  Last edited:
+//    16 Oct 2026, 11:11:09
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the per-path bandwidth limits.
+//
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::TestServer;
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn limited_files_are_throttled() {
+    let server: TestServer = common::spawn("bandwidth", "bandwidth_limits:\n  '**/*.iso': 32768\n").await;
+    std::fs::create_dir(server.site.join("images")).unwrap();
+    std::fs::write(server.site.join("images/disk.iso"), vec![b'x'; 64 * 1024]).unwrap();
+    std::fs::write(server.site.join("images/disk.txt"), vec![b'x'; 64 * 1024]).unwrap();
+
+    // The bucket starts full, so the second half takes (at least) a second
+    let start: Instant = Instant::now();
+    let res: String = server.request("GET", "/images/disk.iso", "").await;
+    let elapsed: Duration = start.elapsed();
+    assert!(res.starts_with("HTTP/1.1 200") && res.ends_with(&"x".repeat(64 * 1024)), "Expected the full file, got {} bytes", res.len());
+    assert!(elapsed >= Duration::from_millis(900), "Expected the download to be throttled, but it took only {elapsed:?}");
+
+    // Others are not
+    let start: Instant = Instant::now();
+    let res: String = server.request("GET", "/images/disk.txt", "").await;
+    let elapsed: Duration = start.elapsed();
+    assert!(res.ends_with(&"x".repeat(64 * 1024)), "Expected the full file, got {} bytes", res.len());
+    assert!(elapsed < Duration::from_millis(900), "Expected the download not to be throttled, but it took {elapsed:?}");
+}
@@ -0,0 +1,92 @@
+//  BUFFERING.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 06:17:57
+//  Last edited:
+//    16 Oct 2026, 06:17:57
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests reading small files fully into memory (`small_file_threshold`)
+//!   rather than streaming them.
+//
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::TestServer;
+
+
+/***** CONSTANTS *****/
+/// The number of requests sent per configuration in the benchmark.
+const ROUNDS: u32 = 500;
+
+
+
+
+
+/***** HELPERS *****/
+/// Writes a small and a large file to the given server's site.
+///
+/// # Arguments
+/// - `server`: The [`TestServer`] to write the files for.
+fn write_files(server: &TestServer) {
+    std::fs::write(server.site.join("small.css"), "body { color: red; }\n".repeat(64)).unwrap();
+    std::fs::write(server.site.join("large.js"), "console.log('Hello, world!');\n".repeat(4096)).unwrap();
+}
+
+/// Measures the average latency of requesting a file.
+///
+/// # Arguments
+/// - `server`: The [`TestServer`] to send requests to.
+/// - `path`: The path to request.
+///
+/// # Returns
+/// The average duration of a single request.
+async fn measure(server: &TestServer, path: &str) -> Duration {
+    let start: Instant = Instant::now();
+    for _ in 0..ROUNDS {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    }
+    start.elapsed() / ROUNDS
+}
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn files_are_sent_in_full() {
+    let server: TestServer = common::spawn("buffering", "small_file_threshold: 4096\n").await;
+    write_files(&server);
+
+    for (path, len) in [("/small.css", 21 * 64), ("/large.js", 30 * 4096), ("/index.html", 13)] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.to_ascii_lowercase().contains(&format!("\r\ncontent-length: {len}\r\n")), "Expected {len} bytes for '{path}', got {res:?}");
+        let body: &str = res.split_once("\r\n\r\n").map(|(_, body)| body).unwrap_or("");
+        assert_eq!(body.len(), len, "Expected the full body for '{path}'");
+    }
+
+    // Ranges of small files are still honoured
+    let res: String = server.request("GET", "/small.css", "Range: bytes=0-3\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 206") && res.ends_with("\r\n\r\nbody"), "Expected the first 4 bytes, got {res:?}");
+}
+
+/// Compares the latency of small files with and without buffering. Timing-dependent, so only run on demand with
+/// `cargo test --test buffering -- --ignored --nocapture`.
+#[tokio::test]
+#[ignore]
+async fn benchmark_small_files() {
+    let streamed: TestServer = common::spawn("buffering-bench-streamed", "").await;
+    let buffered: TestServer = common::spawn("buffering-bench-buffered", "small_file_threshold: 65536\n").await;
+    write_files(&streamed);
+    write_files(&buffered);
+
+    let (streamed, buffered): (Duration, Duration) = (measure(&streamed, "/small.css").await, measure(&buffered, "/small.css").await);
+    println!("Small file over {ROUNDS} requests: {streamed:?} streamed vs. {buffered:?} buffered on average");
+}
@@ -0,0 +1,138 @@
+//  MOD.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 01:47:03
+//  Last edited:
+//    17 Oct 2026, 03:19:03
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Shared harness for the integration tests, which runs a server on a
+//!   throwaway site directory.
+//
+
+// Not every test uses every part of the harness
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use static_website_host::server;
+use static_website_host::state::Context;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+
+/***** CONSTANTS *****/
+/// The time we give the server to respond.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+
+
+
+
+/***** LIBRARY *****/
+/// A server running on an ephemeral port, which is stopped when this is dropped.
+pub struct TestServer {
+    /// The address on which the server is listening.
+    pub addr:   SocketAddr,
+    /// The directory with the config and not found file, which contains the site directory.
+    pub dir:    PathBuf,
+    /// The site directory, which contains a single `index.html` by default.
+    pub site:   PathBuf,
+    /// The task running the accept loop.
+    handle: JoinHandle<()>,
+}
+impl Drop for TestServer {
+    #[inline]
+    fn drop(&mut self) {
+        self.handle.abort();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+impl TestServer {
+    /// Sends a raw HTTP/1.1 request for the given path and reads the full response.
+    ///
+    /// # Arguments
+    /// - `method`: The method of the request.
+    /// - `path`: The (raw) target of the request.
+    /// - `headers`: Any additional headers, each terminated by CRLF.
+    ///
+    /// # Returns
+    /// The full response, including the status line and headers.
+    pub async fn request(&self, method: &str, path: &str, headers: &str) -> String {
+        let raw: String = format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{headers}\r\n");
+        self.send(raw.as_bytes()).await
+    }
+
+    /// Sends raw bytes and reads until the server closes the connection.
+    ///
+    /// # Arguments
+    /// - `raw`: The bytes to send.
+    ///
+    /// # Returns
+    /// Everything the server sent back (lossily converted to a string).
+    pub async fn send(&self, raw: &[u8]) -> String {
+        let mut stream: TcpStream = TcpStream::connect(self.addr).await.unwrap();
+        stream.write_all(raw).await.unwrap();
+        let mut res: Vec<u8> = Vec::new();
+        let _ = tokio::time::timeout(TIMEOUT, stream.read_to_end(&mut res)).await.expect("Server did not close the connection in time");
+        String::from_utf8_lossy(&res).into()
+    }
+}
+
+
+
+/// Loads the config of a test in a fresh site directory with a single `index.html`, without serving it.
+///
+/// # Arguments
+/// - `name`: A name for the test, which makes its directory unique.
+/// - `config`: Additional YAML that is appended to the config file (besides the `site` and, unless given, the `not_found_file`). Any
+///   `{dir}` in it is replaced by the test's directory.
+///
+/// # Returns
+/// A tuple of the test's directory (which the caller should remove), the site directory within it and the loaded [`Context`].
+pub fn load(name: &str, config: &str) -> (PathBuf, PathBuf, Context) {
+    // Prepare a fresh directory
+    let dir: PathBuf = std::env::temp_dir().join(format!("static-website-host-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let site: PathBuf = dir.join("site");
+    std::fs::create_dir_all(&site).unwrap();
+    std::fs::write(site.join("index.html"), "Hello, world!").unwrap();
+    std::fs::write(dir.join("not_found.html"), "Not found").unwrap();
+    let config_path: PathBuf = dir.join("config.yml");
+    let config: String = config.replace("{dir}", &dir.display().to_string());
+    let not_found_file: String =
+        if config.contains("not_found_file:") { String::new() } else { format!("not_found_file: '{}'\n", dir.join("not_found.html").display()) };
+    std::fs::write(&config_path, format!("site: '{}'\n{not_found_file}{config}", site.display())).unwrap();
+    let state: Context = Context::new("static-website-host-test", "0.0.0", &config_path).unwrap();
+    (dir, site, state)
+}
+
+/// Spawns a server on an ephemeral port and a fresh site directory with a single `index.html`.
+///
+/// # Arguments
+/// - `name`: A name for the test, which makes its directory unique.
+/// - `config`: Additional YAML that is appended to the config file (see [`load()`]).
+///
+/// # Returns
+/// A [`TestServer`] that runs until it is dropped.
+pub async fn spawn(name: &str, config: &str) -> TestServer {
+    let (dir, site, state): (PathBuf, PathBuf, Context) = load(name, config);
+
+    // Bind on an ephemeral port, then serve on it
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr: SocketAddr = listener.local_addr().unwrap();
+    let handle: JoinHandle<()> = tokio::spawn(async move {
+        if let Err(err) = server::serve_on(listener, Arc::new(state)).await {
+            panic!("Server failed: {err}");
+        }
+    });
+    TestServer { addr, dir, site, handle }
+}
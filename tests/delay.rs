@@ -0,0 +1,39 @@
+//  DELAY.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 20:28:24
+//  Last edited:
+//    16 Oct 2026, 20:28:24
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the artificial delays that simulate a slow origin.
+//
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::TestServer;
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn matching_paths_are_delayed() {
+    let server: TestServer = common::spawn("delay", "artificial_delay:\n  min_ms: 1000\n  max_ms: 1200\n  paths: [ 'slow' ]\n").await;
+    std::fs::create_dir(server.site.join("slow")).unwrap();
+    std::fs::write(server.site.join("slow/page.html"), "Slow").unwrap();
+
+    let start: Instant = Instant::now();
+    let res: String = server.request("GET", "/slow/page.html", "").await;
+    assert!(res.ends_with("\r\n\r\nSlow"), "Expected the file, got {res:?}");
+    assert!(start.elapsed() >= Duration::from_millis(1000), "Expected the response to be delayed, took {:?}", start.elapsed());
+
+    // Other paths are not
+    let start: Instant = Instant::now();
+    let res: String = server.request("GET", "/index.html", "").await;
+    assert!(res.ends_with("\r\n\r\nHello, world!"), "Expected the file, got {res:?}");
+    assert!(start.elapsed() < Duration::from_millis(1000), "Expected the response not to be delayed, took {:?}", start.elapsed());
+}
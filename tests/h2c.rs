@@ -0,0 +1,70 @@
+//  H2C.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 01:47:03
+//  Last edited:
+//    16 Oct 2026, 15:35:12
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests how connections starting with the HTTP/2 connection preface are
+//!   treated on the plaintext port.
+//
+
+mod common;
+
+use common::{TestServer, TIMEOUT};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::TcpStream;
+
+
+/***** CONSTANTS *****/
+/// The HTTP/2 connection preface, followed by an empty SETTINGS frame.
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00";
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn prior_knowledge_is_spoken_by_default() {
+    let server: TestServer = common::spawn("h2c-default", "").await;
+    let mut stream: TcpStream = TcpStream::connect(server.addr).await.unwrap();
+    stream.write_all(PREFACE).await.unwrap();
+
+    // The server must answer with its own SETTINGS frame
+    let mut frame: [u8; 9] = [0; 9];
+    tokio::time::timeout(TIMEOUT, stream.read_exact(&mut frame)).await.unwrap().unwrap();
+    assert_eq!(frame[3], 0x4, "Expected a SETTINGS frame, got frame type {:#x}", frame[3]);
+}
+
+#[tokio::test]
+async fn prior_knowledge_is_closed_if_http2_is_disabled() {
+    let server: TestServer = common::spawn("h2c-disabled", "http_versions: [ 'http1' ]\n").await;
+
+    // The server must close the connection without serving anything
+    let res: String = server.send(PREFACE).await;
+    assert!(!res.starts_with("HTTP/1.1 200"), "Expected no successful response, got {res:?}");
+}
+
+#[tokio::test]
+async fn http1_is_closed_if_disabled() {
+    let server: TestServer = common::spawn("h1-disabled", "http_versions: [ 'http2' ]\n").await;
+
+    // The server must close the connection without an HTTP/1 response
+    let res: String = server.request("GET", "/", "").await;
+    assert!(!res.starts_with("HTTP/"), "Expected no HTTP/1 response, got {res:?}");
+}
+
+#[tokio::test]
+async fn http1_is_closed_after_one_response_without_keep_alive() {
+    let server: TestServer = common::spawn("h1-no-keep-alive", "keep_alive: false\n").await;
+
+    // Only the first of two pipelined requests must be answered
+    let res: String = server.send(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\nGET / HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+    assert!(res.to_ascii_lowercase().contains("\r\nconnection: close\r\n"), "Expected the connection to be closed, got {res:?}");
+    assert_eq!(res.matches("HTTP/1.1 200").count(), 1, "Expected a single response, got {res:?}");
+}
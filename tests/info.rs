@@ -0,0 +1,54 @@
+//  INFO.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 08:37:42
+//  Last edited:
+//    16 Oct 2026, 10:42:00
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the auxiliary paths reporting information about the server.
+//
+
+mod common;
+
+use common::TestServer;
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn version_answers_get_head_and_options() {
+    let server: TestServer = common::spawn("info-version", "version_path: '/version'\n").await;
+
+    let res: String = server.request("GET", "/version", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK for GET, got {res:?}");
+    assert!(res.contains("\"name\":\"static-website-host-test\""), "Expected the server's name, got {res:?}");
+
+    let res: String = server.request("HEAD", "/version", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK for HEAD, got {res:?}");
+    assert!(res.ends_with("\r\n\r\n"), "Expected no body for HEAD, got {res:?}");
+
+    let res: String = server.request("OPTIONS", "/version", "").await;
+    assert!(res.starts_with("HTTP/1.1 204"), "Expected 204 NO CONTENT for OPTIONS, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("allow: get, head, options\r\n"), "Expected an Allow-header, got {res:?}");
+}
+
+#[tokio::test]
+async fn options_reflect_the_path() {
+    let server: TestServer = common::spawn("info-options", "version_path: '/version'\nget_alias_methods: [ 'QUERY' ]\n").await;
+
+    for path in ["/", "/index.html", "/missing.html"] {
+        let res: String = server.request("OPTIONS", path, "").await;
+        assert!(res.starts_with("HTTP/1.1 204"), "Expected 204 NO CONTENT for '{path}', got {res:?}");
+        assert!(res.to_ascii_lowercase().contains("allow: get, head, query, options\r\n"), "Expected the site's methods, got {res:?}");
+    }
+    let res: String = server.request("OPTIONS", "/version", "").await;
+    assert!(res.to_ascii_lowercase().contains("allow: get, head, options\r\n"), "Expected the version path's methods, got {res:?}");
+
+    // Other methods are refused with the same list
+    let res: String = server.request("DELETE", "/index.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 405"), "Expected 405 METHOD NOT ALLOWED, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("allow: get, head, query, options\r\n"), "Expected the site's methods, got {res:?}");
+}
@@ -0,0 +1,94 @@
+//  LIVERELOAD.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 18:40:57
+//  Last edited:
+//    17 Oct 2026, 05:16:30
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the Server-Sent Events that tell browsers to reload.
+//
+
+mod common;
+
+use std::time::Duration;
+
+use common::{TestServer, TIMEOUT};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::TcpStream;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reads from a stream until everything read so far contains the given text.
+///
+/// # Arguments
+/// - `stream`: The stream to read from.
+/// - `res`: Everything read so far, which is extended.
+/// - `needle`: The text to wait for.
+async fn read_until(stream: &mut TcpStream, res: &mut Vec<u8>, needle: &str) {
+    tokio::time::timeout(TIMEOUT, async {
+        while !String::from_utf8_lossy(res).contains(needle) {
+            let mut buf: [u8; 1024] = [0; 1024];
+            let n: usize = stream.read(&mut buf).await.unwrap();
+            assert!(n > 0, "Server closed the connection before sending {needle:?}, got {:?}", String::from_utf8_lossy(res));
+            res.extend_from_slice(&buf[..n]);
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("Expected {needle:?} in time, got {:?}", String::from_utf8_lossy(res)));
+}
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn changes_are_pushed() {
+    let server: TestServer = common::spawn("livereload", "livereload:\n  poll_interval_ms: 50\n").await;
+
+    let mut stream: TcpStream = TcpStream::connect(server.addr).await.unwrap();
+    stream.write_all(b"GET /_livereload HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+    let mut res: Vec<u8> = Vec::new();
+    read_until(&mut stream, &mut res, ": connected\n\n").await;
+    let head: String = String::from_utf8_lossy(&res).to_ascii_lowercase();
+    assert!(head.starts_with("http/1.1 200"), "Expected 200 OK, got {head:?}");
+    assert!(head.contains("\r\ncontent-type: text/event-stream\r\n"), "Expected an event stream, got {head:?}");
+    assert!(!head.contains("data: reload"), "Expected no reload before anything changed, got {head:?}");
+
+    // Give the watcher the time to look at the site as it was, then change it
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    std::fs::write(server.site.join("app.css"), "body {}").unwrap();
+    read_until(&mut stream, &mut res, "data: reload\n\n").await;
+}
+
+#[tokio::test]
+async fn script_is_injected() {
+    let server: TestServer = common::spawn("livereload-script", "livereload: {}\n").await;
+    std::fs::write(server.site.join("page.html"), "<html><body><p>Hi</p></BODY></html>").unwrap();
+    std::fs::write(server.site.join("app.js"), "</body>").unwrap();
+
+    let res: String = server.request("GET", "/page.html", "").await;
+    let script: &str = "<script>new EventSource(\"/_livereload\").onmessage = () => location.reload();</script>";
+    assert!(
+        res.ends_with(&format!("\r\n\r\n<html><body><p>Hi</p>{script}</BODY></html>")),
+        "Expected the script before </body>, got {res:?}"
+    );
+    assert!(res.to_ascii_lowercase().contains("\r\naccept-ranges: none\r\n"), "Expected no range support, got {res:?}");
+
+    // Pages without a body get it at the end, and other files never do
+    let res: String = server.request("GET", "/index.html", "").await;
+    assert!(res.ends_with(&format!("\r\n\r\nHello, world!{script}")), "Expected the script at the end, got {res:?}");
+    let res: String = server.request("GET", "/app.js", "").await;
+    assert!(res.ends_with("\r\n\r\n</body>"), "Expected the untouched file, got {res:?}");
+}
+
+#[tokio::test]
+async fn disabled_by_default() {
+    let server: TestServer = common::spawn("livereload-disabled", "").await;
+    let res: String = server.request("GET", "/_livereload", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+}
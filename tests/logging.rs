@@ -0,0 +1,42 @@
+//  LOGGING.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 12:29:27
+//  Last edited:
+//    16 Oct 2026, 12:29:27
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the log [`Filter`] in isolation.
+//
+
+use log::LevelFilter;
+use static_website_host::logging::{self, Filter};
+
+
+/***** TESTS *****/
+#[test]
+fn targets_are_filtered_by_prefix() {
+    let filter: Filter = Filter::parse("swh::www=debug, swh=warn,info").unwrap();
+    assert_eq!(filter.level(logging::WWW), LevelFilter::Debug);
+    assert_eq!(filter.level(logging::SRI), LevelFilter::Warn);
+    assert_eq!(filter.level(logging::MAIN), LevelFilter::Warn);
+    assert_eq!(filter.level("swh::wwwx"), LevelFilter::Warn);
+    assert_eq!(filter.level("hyper::proto"), LevelFilter::Info);
+    assert_eq!(filter.max_level(), LevelFilter::Debug);
+}
+
+#[test]
+fn last_directive_wins() {
+    let filter: Filter = Filter::parse("swh::sri=trace,swh::sri=off").unwrap();
+    assert_eq!(filter.level(logging::SRI), LevelFilter::Off);
+    assert_eq!(filter.level(logging::WWW), LevelFilter::Trace);
+}
+
+#[test]
+fn unknown_levels_are_refused() {
+    assert!(Filter::parse("swh::www=loud").is_err());
+    assert!(Filter::parse("").is_ok());
+}
@@ -0,0 +1,93 @@
+//  MATCHER.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 05:38:48
+//  Last edited:
+//    16 Oct 2026, 05:38:48
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the [`PathMatcher`] in isolation.
+//
+
+use std::path::Path;
+
+use static_website_host::matcher::PathMatcher;
+
+
+/***** HELPERS *****/
+/// Builds a matcher whose values are the patterns themselves.
+///
+/// # Arguments
+/// - `patterns`: The patterns to compile.
+///
+/// # Returns
+/// A [`PathMatcher`] that returns the matching pattern.
+fn matcher<'p>(patterns: &[&'p str]) -> PathMatcher<&'p str> { PathMatcher::new(patterns.iter().map(|pattern| (*pattern, *pattern))) }
+
+
+
+
+
+/***** TESTS *****/
+#[test]
+fn prefixes_match_by_component() {
+    let matcher: PathMatcher<&str> = matcher(&["/docs/", "blog"]);
+    assert_eq!(matcher.find(Path::new("docs")), Some(&"/docs/"));
+    assert_eq!(matcher.find(Path::new("docs/api/index.html")), Some(&"/docs/"));
+    assert_eq!(matcher.find(Path::new("blog/2024")), Some(&"blog"));
+    assert_eq!(matcher.find(Path::new("docsy/index.html")), None);
+    assert_eq!(matcher.find(Path::new("")), None);
+}
+
+#[test]
+fn longest_prefix_wins() {
+    let matcher: PathMatcher<&str> = matcher(&["docs", "docs/api", ""]);
+    assert_eq!(matcher.find(Path::new("docs/api/v1")), Some(&"docs/api"));
+    assert_eq!(matcher.find(Path::new("docs/guide")), Some(&"docs"));
+    assert_eq!(matcher.find(Path::new("other")), Some(&""));
+}
+
+#[test]
+fn wildcards_match_within_components() {
+    let matcher: PathMatcher<&str> = matcher(&["*/drafts", "assets/*.css", "v?"]);
+    assert_eq!(matcher.find(Path::new("blog/drafts/post.html")), Some(&"*/drafts"));
+    assert_eq!(matcher.find(Path::new("assets/style.css")), Some(&"assets/*.css"));
+    assert_eq!(matcher.find(Path::new("assets/style.js")), None);
+    assert_eq!(matcher.find(Path::new("v1/index.html")), Some(&"v?"));
+    assert_eq!(matcher.find(Path::new("v10/index.html")), None);
+}
+
+#[test]
+fn double_stars_match_any_depth() {
+    let matcher: PathMatcher<&str> = matcher(&["**/private"]);
+    assert_eq!(matcher.find(Path::new("private/key")), Some(&"**/private"));
+    assert_eq!(matcher.find(Path::new("a/b/c/private")), Some(&"**/private"));
+    assert_eq!(matcher.find(Path::new("a/b/public")), None);
+}
+
+#[test]
+fn literals_beat_equally_long_wildcards() {
+    let matcher: PathMatcher<&str> = matcher(&["docs/*", "docs/api", "docs/**"]);
+    assert_eq!(matcher.find(Path::new("docs/api/v1")), Some(&"docs/api"));
+    assert_eq!(matcher.find(Path::new("docs/guide")), Some(&"docs/*"));
+    // `docs/**` has a single component besides `**`, so `docs/*` is more specific
+    assert_eq!(matcher.find(Path::new("docs/guide/intro")), Some(&"docs/*"));
+}
+
+#[test]
+fn longer_wildcards_beat_shorter_literals() {
+    let matcher: PathMatcher<&str> = matcher(&["blog", "blog/*/drafts"]);
+    assert_eq!(matcher.find(Path::new("blog/2024/drafts/a")), Some(&"blog/*/drafts"));
+    assert_eq!(matcher.find(Path::new("blog/2024/posts/a")), Some(&"blog"));
+}
+
+#[test]
+fn ties_are_won_by_the_first_pattern() {
+    let matcher: PathMatcher<&str> = matcher(&["*/b", "a/*"]);
+    assert_eq!(matcher.find(Path::new("a/b")), Some(&"*/b"));
+    assert!(!matcher.is_empty());
+    assert!(PathMatcher::<()>::default().is_empty());
+}
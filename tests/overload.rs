@@ -0,0 +1,40 @@
+//  OVERLOAD.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 16:53:30
+//  Last edited:
+//    16 Oct 2026, 16:53:30
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the [`OverloadBreaker`] in isolation.
+//
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use static_website_host::state::{InFlight, OverloadBreaker, OverloadProtection};
+
+
+/***** TESTS *****/
+#[test]
+fn slow_requests_trip_the_breaker() {
+    let breaker: Arc<OverloadBreaker> =
+        Arc::new(OverloadBreaker::new(OverloadProtection { latency_ms: 1000, trigger_count: 2, cooldown_seconds: 10 }));
+    let start: Instant = Instant::now();
+
+    // Two requests that get stuck
+    let first: InFlight = breaker.enter(start).unwrap();
+    let second: InFlight = breaker.enter(start + Duration::from_millis(500)).unwrap();
+    let fast: Option<InFlight> = breaker.enter(start + Duration::from_millis(1200));
+    assert!(fast.is_some(), "Expected the breaker to hold while only one request is slow");
+    drop(fast);
+
+    // Once both are slow, new requests are refused for the cooldown, even if the slow ones finish
+    assert!(breaker.enter(start + Duration::from_millis(1500)).is_none(), "Expected the breaker to trip");
+    drop((first, second));
+    assert!(breaker.enter(start + Duration::from_secs(11)).is_none(), "Expected the breaker to stay tripped during the cooldown");
+    assert!(breaker.enter(start + Duration::from_millis(11500)).is_some(), "Expected the breaker to recover after the cooldown");
+}
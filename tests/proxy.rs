@@ -0,0 +1,43 @@
+//  PROXY.rs
+//    by Lut99
+//
+//  Created:
+//    17 Oct 2026, 10:09:42
+//  Last edited:
+//    17 Oct 2026, 10:09:42
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests reading the PROXY protocol headers that load balancers send.
+//
+
+mod common;
+
+use common::{TestServer, TIMEOUT};
+use tokio::io::AsyncReadExt as _;
+use tokio::net::TcpStream;
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn header_is_read() {
+    let server: TestServer = common::spawn("proxy", "proxy_protocol: 'strict'\n").await;
+    let raw: &[u8] = b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\nGET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    let res: String = server.send(raw).await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+}
+
+#[tokio::test]
+async fn silent_connections_are_dropped() {
+    let server: TestServer = common::spawn("proxy-silent", "proxy_protocol: 'strict'\nproxy_header_timeout_ms: 100\n").await;
+
+    // Connect, but never send anything; the server should give up on us well before the harness does
+    let mut stream: TcpStream = TcpStream::connect(server.addr).await.unwrap();
+    let mut res: Vec<u8> = Vec::new();
+    let n: usize = tokio::time::timeout(TIMEOUT, stream.read_to_end(&mut res))
+        .await
+        .expect("Server did not drop the silent connection in time")
+        .unwrap_or(0);
+    assert_eq!(n, 0, "Expected the connection to be closed without a response, got {:?}", String::from_utf8_lossy(&res));
+}
@@ -0,0 +1,59 @@
+//  QUOTA.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 13:08:36
+//  Last edited:
+//    16 Oct 2026, 13:08:36
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the daily quota per client IP.
+//
+
+mod common;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+
+use common::TestServer;
+use static_website_host::state::QuotaTracker;
+
+
+/***** TESTS *****/
+#[test]
+fn windows_reset_after_a_day() {
+    let tracker: QuotaTracker = QuotaTracker::new(100);
+    let (alice, bob): (IpAddr, IpAddr) = (IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)));
+    let start: Instant = Instant::now();
+
+    tracker.record(alice, 60, start);
+    assert_eq!(tracker.check(alice, start), None);
+    tracker.record(alice, 60, start + Duration::from_secs(60));
+    assert_eq!(tracker.check(alice, start + Duration::from_secs(60)), Some(QuotaTracker::WINDOW - Duration::from_secs(60)));
+    assert_eq!(tracker.check(bob, start + Duration::from_secs(60)), None);
+
+    // The window starts with the first bytes, not the last ones
+    assert_eq!(tracker.check(alice, start + QuotaTracker::WINDOW), None);
+    tracker.record(alice, 60, start + QuotaTracker::WINDOW);
+    assert_eq!(tracker.check(alice, start + QuotaTracker::WINDOW), None);
+}
+
+#[tokio::test]
+async fn exceeding_the_quota_is_refused() {
+    let server: TestServer = common::spawn("quota", "daily_quota_bytes: 20\n").await;
+
+    // The index is 13 bytes, so the second request exceeds the quota, but is still sent in full
+    for _ in 0..2 {
+        let res: String = server.request("GET", "/", "").await;
+        assert!(res.starts_with("HTTP/1.1 200") && res.ends_with("\r\n\r\nHello, world!"), "Expected the index, got {res:?}");
+    }
+    let res: String = server.request("GET", "/", "").await;
+    assert!(res.starts_with("HTTP/1.1 429"), "Expected the quota to be exceeded, got {res:?}");
+    let retry_after: u64 = res
+        .split("\r\n")
+        .find_map(|line| line.to_ascii_lowercase().strip_prefix("retry-after: ").map(|secs| secs.trim().parse().unwrap()))
+        .unwrap_or_else(|| panic!("Expected a Retry-After, got {res:?}"));
+    assert!(retry_after > 86400 - 60 && retry_after <= 86400, "Expected to retry in about a day, got {retry_after}s");
+}
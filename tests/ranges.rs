@@ -0,0 +1,109 @@
+//  RANGES.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 14:16:54
+//  Last edited:
+//    17 Oct 2026, 02:10:45
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the boundaries of `Range`-requests, where off-by-one errors
+//!   break download managers.
+//
+
+mod common;
+
+use common::TestServer;
+
+
+/***** HELPERS *****/
+/// Requests a range of `/digits.txt` (which contains `0123456789`) and checks the response.
+///
+/// # Arguments
+/// - `server`: The [`TestServer`] to send the request to.
+/// - `range`: The value of the `Range`-header to send.
+/// - `status`: The status code that is expected.
+/// - `content_range`: The `Content-Range`-header that is expected, if any.
+/// - `body`: The body that is expected.
+async fn expect_range(server: &TestServer, range: &str, status: u16, content_range: Option<&str>, body: &str) {
+    let res: String = server.request("GET", "/digits.txt", &format!("Range: {range}\r\n")).await;
+    let lower: String = res.to_ascii_lowercase();
+    assert!(res.starts_with(&format!("HTTP/1.1 {status} ")), "Expected {status} for '{range}', got {res:?}");
+    match content_range {
+        Some(value) => assert!(lower.contains(&format!("\r\ncontent-range: {value}\r\n")), "Expected '{value}' for '{range}', got {res:?}"),
+        None => assert!(!lower.contains("\r\ncontent-range:"), "Expected no Content-Range for '{range}', got {res:?}"),
+    }
+    assert!(lower.contains(&format!("\r\ncontent-length: {}\r\n", body.len())), "Expected the length of {body:?}, got {res:?}");
+    assert!(res.ends_with(&format!("\r\n\r\n{body}")), "Expected {body:?} for '{range}', got {res:?}");
+}
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn boundary_ranges_are_exact() {
+    let server: TestServer = common::spawn("ranges-boundaries", "").await;
+    std::fs::write(server.site.join("digits.txt"), "0123456789").unwrap();
+
+    expect_range(&server, "bytes=0-0", 206, Some("bytes 0-0/10"), "0").await;
+    expect_range(&server, "bytes=9-9", 206, Some("bytes 9-9/10"), "9").await;
+    expect_range(&server, "bytes=9-", 206, Some("bytes 9-9/10"), "9").await;
+    expect_range(&server, "bytes=8-100", 206, Some("bytes 8-9/10"), "89").await;
+    expect_range(&server, "bytes=-1", 206, Some("bytes 9-9/10"), "9").await;
+    expect_range(&server, "bytes=-100", 206, Some("bytes 0-9/10"), "0123456789").await;
+    expect_range(&server, "bytes=0-99999999999999999999999", 206, Some("bytes 0-9/10"), "0123456789").await;
+}
+
+#[tokio::test]
+async fn degenerate_ranges_are_unsatisfiable() {
+    let server: TestServer = common::spawn("ranges-unsatisfiable", "").await;
+    std::fs::write(server.site.join("digits.txt"), "0123456789").unwrap();
+
+    for range in ["bytes=-0", "bytes=10-", "bytes=10-20", "bytes=-0,10-"] {
+        expect_range(&server, range, 416, Some("bytes */10"), "Range Not Satisfiable").await;
+    }
+
+    // Ranges of empty files never are
+    std::fs::write(server.site.join("digits.txt"), "").unwrap();
+    expect_range(&server, "bytes=0-0", 416, Some("bytes */0"), "Range Not Satisfiable").await;
+}
+
+#[tokio::test]
+async fn malformed_ranges_are_ignored() {
+    let server: TestServer = common::spawn("ranges-malformed", "").await;
+    std::fs::write(server.site.join("digits.txt"), "0123456789").unwrap();
+
+    for range in ["bytes=", "bytes=1-0", "bytes=+0-0", "bytes=0-+1", "bytes=--1", "bytes=0", "items=0-0"] {
+        expect_range(&server, range, 200, None, "0123456789").await;
+    }
+}
+
+#[tokio::test]
+async fn huge_range_lists_are_ignored() {
+    let server: TestServer = common::spawn("ranges-huge", "").await;
+    std::fs::write(server.site.join("digits.txt"), "0123456789").unwrap();
+
+    // Many tiny ranges would otherwise make for an enormous amount of work per request
+    let ranges: Vec<String> = (0..2000).map(|i| format!("{}-{}", i % 10, i % 10)).collect();
+    expect_range(&server, &format!("bytes={}", ranges.join(",")), 200, None, "0123456789").await;
+}
+
+#[tokio::test]
+async fn close_ranges_are_merged() {
+    let server: TestServer = common::spawn("ranges-merged", "").await;
+    std::fs::write(server.site.join("digits.txt"), "0123456789").unwrap();
+    expect_range(&server, "bytes=0-1,2-3,6-7", 206, Some("bytes 0-7/10"), "01234567").await;
+
+    // Ranges that are far apart are still sent as separate parts
+    let digits: String = "0123456789".repeat(100);
+    std::fs::write(server.site.join("digits.txt"), &digits).unwrap();
+    let res: String = server.request("GET", "/digits.txt", "Range: bytes=0-1,900-901\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 206 "), "Expected 206 PARTIAL CONTENT, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("\r\ncontent-type: multipart/byteranges; boundary="), "Expected multiple parts, got {res:?}");
+    assert!(res.contains("\r\nContent-Range: bytes 0-1/1000\r\n\r\n01\r\n--"), "Expected the first part, got {res:?}");
+    assert!(res.contains("\r\nContent-Range: bytes 900-901/1000\r\n\r\n01\r\n--"), "Expected the second part, got {res:?}");
+}
@@ -0,0 +1,86 @@
+//  REDIRECTS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 04:59:39
+//  Last edited:
+//    16 Oct 2026, 04:59:39
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests redirects loaded from a separate file.
+//
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestServer;
+use static_website_host::redirects::Redirects;
+
+
+/***** HELPERS *****/
+/// Extracts the `Location`-header from a raw response.
+///
+/// # Arguments
+/// - `res`: The raw response.
+///
+/// # Returns
+/// The value of the `Location`-header, or an empty string if there is none.
+fn location(res: &str) -> &str {
+    res.split("\r\n").find_map(|line| line.strip_prefix("location: ").or_else(|| line.strip_prefix("Location: "))).unwrap_or("")
+}
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn yaml_redirects_are_followed() {
+    let path: PathBuf = std::env::temp_dir().join(format!("static-website-host-redirects-{}.yml", std::process::id()));
+    std::fs::write(
+        &path,
+        "- from: '/old.html'\n  to: '/new.html'\n- from: '/blog/*'\n  to: '/posts/*'\n  status: 302\n- from: '/blog/pinned'\n  to: '/pinned?from=blog'\n  \
+         status: 308\n",
+    )
+    .unwrap();
+    let server: TestServer = common::spawn("redirects-yaml", &format!("redirects: '{}'\n", path.display())).await;
+
+    let res: String = server.request("GET", "/old.html?a=b", "").await;
+    assert!(res.starts_with("HTTP/1.1 301"), "Expected 301 MOVED PERMANENTLY, got {res:?}");
+    assert_eq!(location(&res), "/new.html?a=b");
+    let res: String = server.request("GET", "/blog/2024/hello", "").await;
+    assert!(res.starts_with("HTTP/1.1 302"), "Expected 302 FOUND, got {res:?}");
+    assert_eq!(location(&res), "/posts/2024/hello");
+    let res: String = server.request("GET", "/blog/pinned?a=b", "").await;
+    assert!(res.starts_with("HTTP/1.1 308"), "Expected 308 PERMANENT REDIRECT, got {res:?}");
+    assert_eq!(location(&res), "/pinned?from=blog");
+    let res: String = server.request("GET", "/index.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn csv_redirects_are_parsed() {
+    let path: PathBuf = std::env::temp_dir().join(format!("static-website-host-redirects-{}.csv", std::process::id()));
+    std::fs::write(&path, "from,to,status\n# Old pages\n/a,/b\n/c/*,https://example.com/*,307\n").unwrap();
+    let redirects: Redirects = Redirects::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(redirects.len(), 2);
+    assert_eq!(redirects.lookup("/a", None).map(|(code, to)| (code.as_u16(), to)), Some((301, "/b".into())));
+    assert_eq!(redirects.lookup("/c/d", Some("e=f")).map(|(code, to)| (code.as_u16(), to)), Some((307, "https://example.com/d?e=f".into())));
+    assert_eq!(redirects.lookup("/d", None), None);
+}
+
+#[test]
+fn illegal_redirects_are_refused() {
+    let path: PathBuf = std::env::temp_dir().join(format!("static-website-host-redirects-illegal-{}.csv", std::process::id()));
+    for raw in ["old,/new\n", "/old,/new,200\n", "/old\n"] {
+        std::fs::write(&path, raw).unwrap();
+        assert!(Redirects::load(&path).is_err(), "Expected {raw:?} to be refused");
+    }
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,127 @@
+//  RESOLUTION.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:53:54
+//  Last edited:
+//    17 Oct 2026, 06:24:48
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the configurable order in which request paths are resolved to
+//!   files.
+//
+
+mod common;
+
+use common::TestServer;
+
+
+/***** HELPERS *****/
+/// Spawns a server with the given resolution order, on a site where both `guide.html` and `guide/index.html` exist.
+///
+/// # Arguments
+/// - `name`: A name for the test, which makes its directory unique.
+/// - `order`: The resolution steps to configure.
+///
+/// # Returns
+/// A [`TestServer`] serving `Suffix` for `guide.html` and `Index` for `guide/index.html`.
+async fn spawn(name: &str, order: &[&str]) -> TestServer {
+    let server: TestServer = common::spawn(name, &format!("resolution_order: [ {} ]\n", order.join(", "))).await;
+    std::fs::write(server.site.join("guide.html"), "Suffix").unwrap();
+    std::fs::create_dir(server.site.join("guide")).unwrap();
+    std::fs::write(server.site.join("guide").join("index.html"), "Index").unwrap();
+    std::fs::write(server.site.join("page"), "Exact").unwrap();
+    std::fs::write(server.site.join("page.html"), "Suffix").unwrap();
+    server
+}
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn every_order_picks_its_first_match() {
+    let permutations: [[&str; 3]; 6] = [
+        ["exact", "html_suffix", "dir_index"],
+        ["exact", "dir_index", "html_suffix"],
+        ["html_suffix", "exact", "dir_index"],
+        ["html_suffix", "dir_index", "exact"],
+        ["dir_index", "exact", "html_suffix"],
+        ["dir_index", "html_suffix", "exact"],
+    ];
+    for (i, order) in permutations.iter().enumerate() {
+        let server: TestServer = spawn(&format!("resolution-{i}"), order).await;
+
+        // `/guide` is never a file, so either the suffix or the index wins
+        let suffix: usize = order.iter().position(|step| *step == "html_suffix").unwrap();
+        let index: usize = order.iter().position(|step| *step == "dir_index").unwrap();
+        let res: String = server.request("GET", "/guide", "").await;
+        let expected: &str = if suffix < index { "Suffix" } else { "Index" };
+        assert!(res.ends_with(&format!("\r\n\r\n{expected}")), "Expected {expected:?} for '/guide' with order {order:?}, got {res:?}");
+
+        // `/page` is never a directory, so either the exact file or the suffix wins
+        let exact: usize = order.iter().position(|step| *step == "exact").unwrap();
+        let res: String = server.request("GET", "/page", "").await;
+        let expected: &str = if exact < suffix { "Exact" } else { "Suffix" };
+        assert!(res.ends_with(&format!("\r\n\r\n{expected}")), "Expected {expected:?} for '/page' with order {order:?}, got {res:?}");
+    }
+}
+
+#[tokio::test]
+async fn missing_steps_are_not_tried() {
+    let server: TestServer = spawn("resolution-exact-only", &["exact"]).await;
+    for path in ["/guide", "/"] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND for '{path}', got {res:?}");
+    }
+    let res: String = server.request("GET", "/guide.html", "").await;
+    assert!(res.ends_with("\r\n\r\nSuffix"), "Expected the exact file, got {res:?}");
+}
+
+#[tokio::test]
+async fn default_order_ignores_suffix() {
+    let server: TestServer = common::spawn("resolution-default", "").await;
+    std::fs::write(server.site.join("about.html"), "About").unwrap();
+    let res: String = server.request("GET", "/about", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+}
+
+#[tokio::test]
+async fn crawlers_get_bot_files() {
+    // NOTE: Bot files must exist when loading the config, so use the index as the "prerendered" page
+    let server: TestServer = common::spawn(
+        "resolution-bots",
+        "bot_user_agents: [ 'Googlebot' ]\nbot_files:\n  'app': 'index.html'\nresolution_order: [ 'exact', 'html_suffix' ]\n",
+    )
+    .await;
+    std::fs::write(server.site.join("app.html"), "Shell").unwrap();
+    std::fs::write(server.site.join("other.html"), "Other").unwrap();
+
+    let res: String = server.request("GET", "/app", "User-Agent: Mozilla/5.0 (compatible; googlebot/2.1)\r\n").await;
+    assert!(res.ends_with("\r\n\r\nHello, world!"), "Expected the bot file, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("\r\nvary: user-agent\r\n"), "Expected to vary on the user agent, got {res:?}");
+
+    // Browsers, and other paths, are resolved normally
+    let res: String = server.request("GET", "/app", "User-Agent: Mozilla/5.0 (X11; Linux x86_64) Firefox/130.0\r\n").await;
+    assert!(res.ends_with("\r\n\r\nShell"), "Expected the normal file, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("\r\nvary: user-agent\r\n"), "Expected to vary on the user agent, got {res:?}");
+    let res: String = server.request("GET", "/other", "User-Agent: Googlebot\r\n").await;
+    assert!(res.ends_with("\r\n\r\nOther"), "Expected the normal file, got {res:?}");
+    assert!(!res.to_ascii_lowercase().contains("\r\nvary:"), "Expected no Vary, got {res:?}");
+}
+
+#[tokio::test]
+async fn debug_headers_name_the_resolution() {
+    let config: &str = "debug_headers: true\nresolution_order: [ exact, html_suffix, dir_index ]\n";
+    let server: TestServer = common::spawn("resolution-debug-headers", config).await;
+    std::fs::write(server.site.join("about.html"), "About").unwrap();
+
+    for (path, resolution) in [("/index.html", "direct"), ("/about", "clean-url"), ("/", "index")] {
+        let res: String = server.request("GET", path, "").await;
+        let expected: String = format!("\r\nx-resolution: {resolution}\r\n");
+        assert!(res.to_ascii_lowercase().contains(&expected), "Expected '{resolution}' for '{path}', got {res:?}");
+    }
+}
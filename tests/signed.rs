@@ -0,0 +1,83 @@
+//  SIGNED.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 06:57:06
+//  Last edited:
+//    17 Oct 2026, 03:19:03
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests signed URLs, both in isolation and through the server.
+//
+
+mod common;
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::TestServer;
+use static_website_host::signed::{self, Signature};
+use static_website_host::state::Context;
+
+
+/***** TESTS *****/
+#[test]
+fn signatures_are_hmac_sha256() {
+    // Computed independently with `printf '%s\n%s' /downloads/a.zip 1767225600 | openssl dgst -sha256 -hmac secret`
+    assert_eq!(
+        signed::sign("secret", "/downloads/a.zip", 1767225600),
+        "expires=1767225600&sig=b0c63be05f642f82a11bd25212347b6d6e81751703e8d0c2b887b8b8b3685a49"
+    );
+}
+
+#[test]
+fn signatures_are_checked() {
+    let check = |path: &str, query: Option<&str>, now: u64| signed::check("secret", path, query, now);
+    let query: String = signed::sign("secret", "/downloads/a.zip", 1000);
+    assert_eq!(check("/downloads/a.zip", Some(&query), 1000), Signature::Valid);
+    assert_eq!(check("/downloads/a.zip", Some(&format!("foo=bar&{query}")), 999), Signature::Valid);
+    let (expires, sig): (&str, &str) = query.split_once("&sig=").unwrap();
+    assert_eq!(check("/downloads/a.zip", Some(&format!("{expires}&sig={}", sig.to_uppercase())), 1000), Signature::Valid);
+    assert_eq!(check("/downloads/a.zip", Some(&query), 1001), Signature::Expired);
+    assert_eq!(signed::check("other", "/downloads/a.zip", Some(&query), 1000), Signature::Invalid);
+    assert_eq!(check("/downloads/b.zip", Some(&query), 1000), Signature::Invalid);
+    assert_eq!(check("/downloads/a.zip", Some(&query.replace("1000", "2000")), 1000), Signature::Invalid);
+    assert_eq!(check("/downloads/a.zip", Some("expires=1000"), 1000), Signature::Missing);
+    assert_eq!(check("/downloads/a.zip", Some("expires=1000&sig=xyz"), 1000), Signature::Missing);
+    assert_eq!(check("/downloads/a.zip", None, 1000), Signature::Missing);
+}
+
+#[tokio::test]
+async fn signed_paths_require_a_signature() {
+    let server: TestServer = common::spawn("signed", "signed_urls:\n  secret: 'secret'\n  paths: [ 'downloads' ]\n").await;
+    std::fs::create_dir(server.site.join("downloads")).unwrap();
+    std::fs::write(server.site.join("downloads/a.zip"), "Archive").unwrap();
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    // Only valid, unexpired signatures get through
+    let res: String = server.request("GET", &format!("/downloads/a.zip?{}", signed::sign("secret", "/downloads/a.zip", now + 60)), "").await;
+    assert!(res.starts_with("HTTP/1.1 200") && res.ends_with("\r\n\r\nArchive"), "Expected the file, got {res:?}");
+    for query in [String::new(), signed::sign("secret", "/downloads/a.zip", now - 60), signed::sign("wrong", "/downloads/a.zip", now + 60)] {
+        let res: String = server.request("GET", &format!("/downloads/a.zip?{query}"), "").await;
+        assert!(res.starts_with("HTTP/1.1 403"), "Expected 403 FORBIDDEN for {query:?}, got {res:?}");
+    }
+    // Also through other spellings of the same path
+    let res: String = server.request("GET", "/./downloads//a.zip", "").await;
+    assert!(res.starts_with("HTTP/1.1 403"), "Expected 403 FORBIDDEN, got {res:?}");
+
+    // Other paths are unaffected
+    let res: String = server.request("GET", "/index.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+}
+
+#[test]
+fn secret_is_not_printed() {
+    let (dir, _, state): (PathBuf, PathBuf, Context) =
+        common::load("signed-print-config", "signed_urls:\n  secret: 'correct-horse-battery'\n  paths: [ 'downloads' ]\n");
+    let config: String = serde_yml::to_string(&state).unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+    assert!(config.contains("<redacted>"), "Expected the secret to be redacted, got {config:?}");
+    assert!(!config.contains("correct-horse-battery"), "Expected no secret in the printed config, got {config:?}");
+}
@@ -0,0 +1,72 @@
+//  SRI.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 09:23:42
+//  Last edited:
+//    17 Oct 2026, 02:39:54
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the manifest of Subresource Integrity hashes.
+//
+
+mod common;
+
+use common::TestServer;
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn manifest_lists_assets() {
+    let server: TestServer = common::spawn("sri", "sri_manifest: {}\n").await;
+    std::fs::create_dir(server.site.join("css")).unwrap();
+    std::fs::write(server.site.join("css/app.css"), "body { color: red; }").unwrap();
+    std::fs::write(server.site.join("app.js"), "").unwrap();
+
+    let res: String = server.request("GET", "/_integrity.json", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(
+        res.ends_with(
+            "\r\n\r\n{\"/app.js\":\"sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb\",\"/css/app.css\":\"sha384-\
+             BN8siYsJqlPeNsRFs2pYbTW0uiUBy9v6JVVKpHaS+KNqD0ZFotD5OFKMkI6/s6sb\"}"
+        ),
+        "Expected the hashes of the CSS and JS files only, got {res:?}"
+    );
+
+    // Changed files are hashed again
+    std::fs::write(server.site.join("css/app.css"), "body { color: blue; }").unwrap();
+    std::fs::remove_file(server.site.join("app.js")).unwrap();
+    let res: String = server.request("GET", "/_integrity.json", "").await;
+    assert!(
+        res.ends_with("\r\n\r\n{\"/css/app.css\":\"sha384-93a8cfRvdHX1FUK4kn0YHdMqH41PfGqWsjY7tQ8Ht+uRxvZzhc+aLQgpz40p2SsM\"}"),
+        "Expected the new hash of the CSS file only, got {res:?}"
+    );
+}
+
+#[tokio::test]
+async fn manifest_skips_files_not_served_as_is() {
+    let config: &str = "sri_manifest:\n  extensions: [ 'html', 'js' ]\nsigned_urls:\n  secret: 'secret'\n  paths: [ 'downloads' ]\ngone_paths: [ \
+                        'old' ]\ngone_file: 'index.html'\nnot_found_file: '{dir}/site/404.html'\n";
+    let server: TestServer = common::spawn("sri-skipped", config).await;
+    for dir in ["downloads", "old"] {
+        std::fs::create_dir(server.site.join(dir)).unwrap();
+        std::fs::write(server.site.join(dir).join("app.js"), "").unwrap();
+    }
+    std::fs::write(server.site.join("app.js"), "").unwrap();
+
+    let res: String = server.request("GET", "/_integrity.json", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(
+        res.ends_with("\r\n\r\n{\"/app.js\":\"sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb\"}"),
+        "Expected no signed, gone or not found files, got {res:?}"
+    );
+}
+
+#[tokio::test]
+async fn manifest_is_absent_by_default() {
+    let server: TestServer = common::spawn("sri-disabled", "").await;
+    let res: String = server.request("GET", "/_integrity.json", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+}
@@ -0,0 +1,42 @@
+//  VARY.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 16:36:39
+//  Last edited:
+//    15 Oct 2026, 16:36:39
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests that negotiation dimensions are combined into a single `Vary`-
+//!   header.
+//
+
+use hyper::header::{self, HeaderMap, HeaderValue};
+use static_website_host::www::VaryBuilder;
+
+
+/***** TESTS *****/
+#[test]
+fn dimensions_are_combined() {
+    let mut headers: HeaderMap = HeaderMap::new();
+    VaryBuilder::new().add("Accept-Encoding").add("Accept-Language").apply(&mut headers);
+    assert_eq!(headers.get(header::VARY).unwrap(), "Accept-Encoding, Accept-Language");
+}
+
+#[test]
+fn existing_vary_is_merged() {
+    let mut headers: HeaderMap = HeaderMap::new();
+    headers.insert(header::VARY, HeaderValue::from_static("Accept, accept-encoding"));
+    VaryBuilder::new().add("Accept-Encoding").add("Accept-Language").apply(&mut headers);
+    assert_eq!(headers.get(header::VARY).unwrap(), "Accept, accept-encoding, Accept-Language");
+    assert_eq!(headers.get_all(header::VARY).iter().count(), 1);
+}
+
+#[test]
+fn nothing_is_sent_without_dimensions() {
+    let mut headers: HeaderMap = HeaderMap::new();
+    VaryBuilder::new().apply(&mut headers);
+    assert!(!headers.contains_key(header::VARY));
+}
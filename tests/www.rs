@@ -0,0 +1,422 @@
+//  WWW.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 07:58:33
+//  Last edited:
+//    17 Oct 2026, 11:28:00
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Tests the basic behaviour of serving files from the site directory.
+//
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestServer;
+use static_website_host::state::Context;
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn existing_file_is_served() {
+    let server: TestServer = common::spawn("www-existing", "").await;
+    let res: String = server.request("GET", "/index.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(res.ends_with("\r\n\r\nHello, world!"), "Expected the file's contents, got {res:?}");
+}
+
+#[tokio::test]
+async fn directory_serves_index() {
+    let server: TestServer = common::spawn("www-index", "").await;
+    let res: String = server.request("GET", "/", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(res.ends_with("\r\n\r\nHello, world!"), "Expected the index file's contents, got {res:?}");
+}
+
+#[tokio::test]
+async fn missing_file_is_not_found() {
+    let server: TestServer = common::spawn("www-missing", "").await;
+    let res: String = server.request("GET", "/does-not-exist.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+    assert!(res.ends_with("\r\n\r\nNot found"), "Expected the not found page, got {res:?}");
+}
+
+#[tokio::test]
+async fn traversal_is_blocked() {
+    let server: TestServer = common::spawn("www-traversal", "").await;
+    for path in ["/../config.yml", "/%2e%2e/config.yml", "/..%2fconfig.yml"] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND for '{path}', got {res:?}");
+        assert!(!res.contains("not_found_file"), "Config leaked for '{path}': {res:?}");
+    }
+}
+
+#[tokio::test]
+async fn index_is_redirected_to_directory() {
+    let server: TestServer = common::spawn("www-index-redirect", "index_redirect: true\n").await;
+    std::fs::create_dir(server.site.join("foo")).unwrap();
+    std::fs::write(server.site.join("foo").join("index.html"), "Foo").unwrap();
+
+    let res: String = server.request("GET", "/foo/index.html?bar=baz", "").await;
+    assert!(res.starts_with("HTTP/1.1 301"), "Expected 301 MOVED PERMANENTLY, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("location: /foo/?bar=baz\r\n"), "Expected a redirect to the directory, got {res:?}");
+
+    let res: String = server.request("GET", "/foo/", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(res.ends_with("\r\n\r\nFoo"), "Expected the index file's contents, got {res:?}");
+}
+
+#[tokio::test]
+async fn index_redirect_stays_on_origin() {
+    let server: TestServer = common::spawn("www-index-redirect-origin", "index_redirect: true\n").await;
+    std::fs::create_dir(server.site.join("evil.com")).unwrap();
+    std::fs::write(server.site.join("evil.com").join("index.html"), "Evil").unwrap();
+
+    let res: String = server.request("GET", "//evil.com/index.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 301"), "Expected 301 MOVED PERMANENTLY, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("location: /evil.com/\r\n"), "Expected a redirect on the same origin, got {res:?}");
+
+    // Index files that don't exist are not redirected to a directory at all
+    let res: String = server.request("GET", "//other.com/index.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+}
+
+#[tokio::test]
+async fn index_redirect_keeps_other_index_files() {
+    let config: &str = "index_redirect: true\nindex_files: [ 'index.html', 'index.htm' ]\n";
+    let server: TestServer = common::spawn("www-index-redirect-multi", config).await;
+    std::fs::create_dir(server.site.join("foo")).unwrap();
+    std::fs::write(server.site.join("foo").join("index.html"), "Html").unwrap();
+    std::fs::write(server.site.join("foo").join("index.htm"), "Htm").unwrap();
+
+    // Only the index file that the directory serves is redirected to it
+    let res: String = server.request("GET", "/foo/index.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 301"), "Expected 301 MOVED PERMANENTLY, got {res:?}");
+    let res: String = server.request("GET", "/foo/index.htm", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(res.ends_with("\r\n\r\nHtm"), "Expected the other index file, got {res:?}");
+}
+
+#[tokio::test]
+async fn index_redirect_keeps_root_index_with_root_file() {
+    let server: TestServer = common::spawn("www-index-redirect-root", "index_redirect: true\nroot_file: 'home.html'\n").await;
+    std::fs::write(server.site.join("home.html"), "Home").unwrap();
+
+    // The root serves the root file, so its index file isn't redirected there
+    let res: String = server.request("GET", "/index.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(res.ends_with("\r\n\r\nHello, world!"), "Expected the index file, got {res:?}");
+}
+
+#[tokio::test]
+async fn acme_challenge_is_served_as_text() {
+    let server: TestServer = common::spawn("www-acme", "acme_challenge:\n  dir: '{dir}/acme'\n").await;
+    std::fs::create_dir(server.dir.join("acme")).unwrap();
+    std::fs::write(server.dir.join("acme").join("abc-DEF_123"), "abc-DEF_123.key").unwrap();
+
+    let res: String = server.request("GET", "/.well-known/acme-challenge/abc-DEF_123", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("content-type: text/plain\r\n"), "Expected plain text, got {res:?}");
+    assert!(res.ends_with("\r\n\r\nabc-DEF_123.key"), "Expected the challenge, got {res:?}");
+
+    let res: String = server.request("GET", "/.well-known/acme-challenge/..%2f..%2fconfig.yml", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+}
+
+#[tokio::test]
+async fn single_file_is_served_for_everything() {
+    let server: TestServer = common::spawn("www-single-file", "single_file: 'index.html'\n").await;
+    for path in ["/", "/foo/bar", "/style.css", "/does-not-exist.html"] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK for '{path}', got {res:?}");
+        assert!(res.ends_with("\r\n\r\nHello, world!"), "Expected the single file for '{path}', got {res:?}");
+    }
+}
+
+#[tokio::test]
+async fn single_file_keeps_guards() {
+    let config: &str = "single_file: 'index.html'\nsigned_urls:\n  secret: 'secret'\n  paths: [ 'downloads' ]\ngone_paths: [ 'old' ]\n";
+    let server: TestServer = common::spawn("www-single-file-guards", config).await;
+    for (path, status) in [("/downloads/a.zip", 403), ("/old/page.html", 410), ("/other", 200)] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.starts_with(&format!("HTTP/1.1 {status} ")), "Expected {status} for '{path}', got {res:?}");
+    }
+}
+
+#[tokio::test]
+async fn preconditions_are_evaluated() {
+    let server: TestServer = common::spawn("www-preconditions", "").await;
+    let res: String = server.request("GET", "/index.html", "").await;
+    let etag: &str = res
+        .split("\r\n")
+        .find_map(|line| line.strip_prefix("etag: ").or_else(|| line.strip_prefix("ETag: ")))
+        .unwrap_or_else(|| panic!("Expected an ETag, got {res:?}"));
+
+    let res: String = server.request("GET", "/index.html", &format!("If-Match: {etag}\r\n")).await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    let res: String = server.request("GET", "/index.html", "If-Match: \"other\"\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 412"), "Expected 412 PRECONDITION FAILED, got {res:?}");
+    let res: String = server.request("GET", "/index.html", "If-Unmodified-Since: Thu, 01 Jan 1970 00:00:00 GMT\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 412"), "Expected 412 PRECONDITION FAILED, got {res:?}");
+    let res: String = server.request("GET", "/index.html", &format!("If-None-Match: {etag}\r\n")).await;
+    assert!(res.starts_with("HTTP/1.1 304"), "Expected 304 NOT MODIFIED, got {res:?}");
+
+    // `If-Match` takes precedence over `If-Unmodified-Since`
+    let res: String =
+        server.request("GET", "/index.html", &format!("If-Match: {etag}\r\nIf-Unmodified-Since: Thu, 01 Jan 1970 00:00:00 GMT\r\n")).await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+}
+
+#[tokio::test]
+async fn content_hash_etags_survive_rewrites() {
+    let server: TestServer = common::spawn("www-content-hash", "etag: 'content_hash'\n").await;
+    let etag = |res: &str| -> String {
+        res.split("\r\n")
+            .find_map(|line| line.strip_prefix("etag: ").or_else(|| line.strip_prefix("ETag: ")))
+            .unwrap_or_else(|| panic!("Expected an ETag, got {res:?}"))
+            .into()
+    };
+    let first: String = etag(&server.request("GET", "/index.html", "").await);
+
+    // Rewriting the same contents keeps it...
+    std::fs::write(server.site.join("index.html"), "Hello, world!").unwrap();
+    let file: std::fs::File = std::fs::File::options().write(true).open(server.site.join("index.html")).unwrap();
+    file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60)).unwrap();
+    assert_eq!(etag(&server.request("GET", "/index.html", "").await), first, "Expected the same ETag for the same contents");
+    let res: String = server.request("GET", "/index.html", &format!("If-None-Match: {first}\r\n")).await;
+    assert!(res.starts_with("HTTP/1.1 304"), "Expected 304 NOT MODIFIED, got {res:?}");
+
+    // ...but other contents don't
+    std::fs::write(server.site.join("index.html"), "Goodbye, world!").unwrap();
+    assert_ne!(etag(&server.request("GET", "/index.html", "").await), first, "Expected a new ETag for new contents");
+}
+
+#[tokio::test]
+async fn stripped_headers_are_removed() {
+    let server: TestServer = common::spawn(
+        "www-strip",
+        "not_found_headers:\n  X-Robots-Tag: 'noindex'\nstrip_response_headers: [ 'Server', 'x-robots-tag' ]\n",
+    )
+    .await;
+    for path in ["/index.html", "/missing.html"] {
+        let res: String = server.request("GET", path, "").await;
+        let lower: String = res.to_ascii_lowercase();
+        assert!(
+            !lower.contains("\r\nserver:") && !lower.contains("\r\nx-robots-tag:"),
+            "Expected the headers to be stripped, got {res:?}"
+        );
+        assert!(lower.contains("\r\ncontent-type:"), "Expected other headers to be kept, got {res:?}");
+    }
+}
+
+#[tokio::test]
+async fn not_found_file_in_site_terminates() {
+    let server: TestServer = common::spawn("www-not-found-in-site", "not_found_file: '{dir}/site/404.html'\n").await;
+    let res: String = server.request("GET", "/404.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+    assert_eq!(res.matches("HTTP/1.1 ").count(), 1, "Expected a single response, got {res:?}");
+    assert!(res.contains("Oops! That page wasn't found on this server."), "Expected the (generated) not found page, got {res:?}");
+
+    // Also if it has disappeared in the meantime
+    std::fs::remove_file(server.site.join("404.html")).unwrap();
+    let res: String = server.request("GET", "/404.html", "").await;
+    assert_eq!(res.matches("HTTP/1.1 ").count(), 1, "Expected a single response, got {res:?}");
+}
+
+#[test]
+fn printed_config_has_resolved_paths() {
+    for not_found_file in ["'{dir}/site/../not_found.html'", "\n  text/html: '{dir}/site/../not_found.html'"] {
+        let config: String = format!("not_found_file: {not_found_file}\nroot_file: './home.html'\n");
+        let (dir, site, state): (PathBuf, PathBuf, Context) = common::load("www-print-config", &config);
+        let printed: String = serde_yml::to_string(&state).unwrap();
+        let (dir, site): (PathBuf, PathBuf) = (std::fs::canonicalize(&dir).unwrap(), std::fs::canonicalize(site).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+        for path in [dir.join("not_found.html"), site.join("home.html")] {
+            assert!(printed.contains(&format!("{}\n", path.display())), "Expected '{}' to be printed, got {printed:?}", path.display());
+        }
+        assert!(!printed.contains("site/..") && !printed.contains("/./"), "Expected no unresolved paths, got {printed:?}");
+    }
+}
+
+#[tokio::test]
+async fn invalid_paths_are_bad_requests() {
+    let server: TestServer = common::spawn("www-invalid-path", "").await;
+    for path in ["/%FF", "/index%FE.html", "/%C0%AE%C0%AE/config.yml", "/%E0%80%AF", "/index.html%00"] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.starts_with("HTTP/1.1 400"), "Expected 400 BAD REQUEST for '{path}', got {res:?}");
+    }
+}
+
+#[tokio::test]
+async fn trace_is_refused() {
+    let server: TestServer = common::spawn("www-trace", "version_path: '/version'\n").await;
+    for path in ["/", "/index.html", "/version"] {
+        let res: String = server.request("TRACE", path, "X-Secret: hunter2\r\n").await;
+        assert!(res.starts_with("HTTP/1.1 405"), "Expected 405 METHOD NOT ALLOWED for '{path}', got {res:?}");
+        assert!(!res.contains("hunter2"), "Request was echoed for '{path}': {res:?}");
+    }
+
+    let server: TestServer = common::spawn("www-trace-forbidden", "trace_status: 403\n").await;
+    let res: String = server.request("TRACE", "/", "").await;
+    assert!(res.starts_with("HTTP/1.1 403"), "Expected 403 FORBIDDEN, got {res:?}");
+}
+
+#[tokio::test]
+async fn connect_is_refused() {
+    let server: TestServer = common::spawn("www-connect", "get_alias_methods: [ 'QUERY' ]\n").await;
+    let res: String = server.send(b"CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\nConnection: close\r\n\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 405"), "Expected 405 METHOD NOT ALLOWED, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("\r\nallow: get, head, query, options\r\n"), "Expected the allowed methods, got {res:?}");
+}
+
+#[tokio::test]
+async fn charset_is_sniffed() {
+    let server: TestServer = common::spawn("www-sniff-charset", "sniff_charset: true\n").await;
+    std::fs::write(server.site.join("meta.html"), "<html><head><META charset=\"ISO-8859-1\"></head></html>").unwrap();
+    std::fs::write(server.site.join("bom.txt"), b"\xFF\xFEh\x00i\x00").unwrap();
+    std::fs::write(server.site.join("feed.xml.txt"), "<?xml version=\"1.0\" encoding='windows-1252'?><feed/>").unwrap();
+    std::fs::write(server.site.join("conflict.html"), "<meta charset=\"utf-8\"><meta charset=\"koi8-r\">").unwrap();
+
+    for (path, charset) in
+        [("/meta.html", "iso-8859-1"), ("/bom.txt", "utf-16le"), ("/feed.xml.txt", "windows-1252"), ("/conflict.html", "utf-8"), ("/index.html", "utf-8")]
+    {
+        let res: String = server.request("GET", path, "").await.to_ascii_lowercase();
+        assert!(res.contains(&format!("; charset={charset}\r\n")), "Expected charset {charset:?} for '{path}', got {res:?}");
+    }
+
+    // The body must still be sent in full
+    let res: String = server.request("GET", "/meta.html", "").await;
+    assert!(res.ends_with("\r\n\r\n<html><head><META charset=\"ISO-8859-1\"></head></html>"), "Expected the full file, got {res:?}");
+}
+
+#[tokio::test]
+async fn removed_content_is_gone() {
+    let server: TestServer = common::spawn("www-gone", "gone_paths: [ 'old', 'promo.html' ]\n").await;
+    std::fs::create_dir(server.site.join("old")).unwrap();
+    std::fs::write(server.site.join("old/page.html"), "Still here").unwrap();
+    for path in ["/old", "/old/page.html", "/old/missing.html", "/promo.html"] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.starts_with("HTTP/1.1 410") && res.ends_with("\r\n\r\nGone"), "Expected 410 GONE for '{path}', got {res:?}");
+    }
+    let res: String = server.request("GET", "/older.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 404"), "Expected 404 NOT FOUND, got {res:?}");
+
+    // The page for it may be customized
+    let server: TestServer = common::spawn("www-gone-file", "gone_paths: [ 'old' ]\ngone_file: 'index.html'\n").await;
+    let res: String = server.request("GET", "/old/page.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 410") && res.ends_with("\r\n\r\nHello, world!"), "Expected the gone page, got {res:?}");
+}
+
+#[tokio::test]
+async fn text_is_transformed() {
+    let server: TestServer = common::spawn("www-transform", "normalize_line_endings: 'lf'\nstrip_bom: true\ntransform_max_size: 64\n").await;
+    std::fs::write(server.site.join("mixed.txt"), b"\xEF\xBB\xBFone\r\ntwo\nthree\r\n").unwrap();
+    std::fs::write(server.site.join("archive.zip"), b"PK\r\n\x03\x04").unwrap();
+    std::fs::write(server.site.join("binary.txt"), b"\x00\r\n").unwrap();
+    std::fs::write(server.site.join("large.txt"), "line\r\n".repeat(16)).unwrap();
+
+    let res: String = server.request("GET", "/mixed.txt", "Range: bytes=0-2\r\n").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected the whole (transformed) file, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("\r\ncontent-length: 14\r\n"), "Expected the transformed length, got {res:?}");
+    assert!(res.ends_with("\r\n\r\none\ntwo\nthree\n"), "Expected the transformed file, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("\r\naccept-ranges: none\r\n"), "Expected ranges to be refused, got {res:?}");
+
+    // Binary and large files are left alone
+    for (path, body) in [("/archive.zip", "PK\r\n\x03\x04"), ("/binary.txt", "\x00\r\n")] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.ends_with(&format!("\r\n\r\n{body}")), "Expected the untouched file for '{path}', got {res:?}");
+    }
+    let res: String = server.request("GET", "/large.txt", "").await;
+    assert!(res.ends_with(&format!("\r\n\r\n{}", "line\r\n".repeat(16))), "Expected the untouched file, got {res:?}");
+    assert!(res.to_ascii_lowercase().contains("\r\naccept-ranges: bytes\r\n"), "Expected ranges to be supported, got {res:?}");
+}
+
+#[tokio::test]
+async fn html_gets_a_csp_nonce() {
+    let server: TestServer = common::spawn("www-csp", "csp_nonce:\n  policy: \"script-src 'nonce-{{csp_nonce}}'\"\n").await;
+    std::fs::write(server.site.join("page.html"), "<script nonce=\"{{csp_nonce}}\"></script>").unwrap();
+    std::fs::write(server.site.join("notes.txt"), "{{csp_nonce}}").unwrap();
+
+    let mut nonces: Vec<String> = Vec::new();
+    for _ in 0..2 {
+        let res: String = server.request("GET", "/page.html", "").await;
+        let lower: String = res.to_ascii_lowercase();
+        assert!(lower.contains("\r\ncache-control: no-store\r\n"), "Expected the response not to be cached, got {res:?}");
+        assert!(!lower.contains("\r\netag:"), "Expected no validators, got {res:?}");
+        let nonce: &str = res
+            .split("\r\n")
+            .find(|line| line.to_ascii_lowercase().starts_with("content-security-policy:"))
+            .and_then(|line| line.split("'nonce-").nth(1))
+            .and_then(|rest| rest.split('\'').next())
+            .unwrap_or_else(|| panic!("Expected a Content-Security-Policy with a nonce, got {res:?}"));
+        assert!(res.ends_with(&format!("\r\n\r\n<script nonce=\"{nonce}\"></script>")), "Expected the nonce in the body, got {res:?}");
+        nonces.push(nonce.into());
+    }
+    assert_ne!(nonces[0], nonces[1], "Expected a fresh nonce for every response");
+
+    // Other files are left alone
+    let res: String = server.request("GET", "/notes.txt", "").await;
+    assert!(!res.to_ascii_lowercase().contains("content-security-policy"), "Expected no policy, got {res:?}");
+    assert!(res.ends_with("\r\n\r\n{{csp_nonce}}"), "Expected the untouched file, got {res:?}");
+}
+
+#[tokio::test]
+async fn large_html_keeps_its_csp() {
+    let config: &str = "csp_nonce:\n  policy: \"script-src 'nonce-{{csp_nonce}}'\"\ntransform_max_size: 64\n";
+    let server: TestServer = common::spawn("www-csp-large", config).await;
+    let page: String = format!("<script nonce=\"{{{{csp_nonce}}}}\"></script>{}", " ".repeat(64));
+    std::fs::write(server.site.join("page.html"), &page).unwrap();
+
+    // The page cannot be rewritten, so its scripts are blocked instead of the whole policy being dropped
+    let res: String = server.request("GET", "/page.html", "").await;
+    assert!(res.starts_with("HTTP/1.1 200"), "Expected 200 OK, got {res:?}");
+    let lower: String = res.to_ascii_lowercase();
+    assert!(lower.contains("\r\ncontent-security-policy: script-src 'nonce-"), "Expected the policy with a nonce, got {res:?}");
+    assert!(!lower.contains("'nonce-{{csp_nonce}}'"), "Expected the placeholder to be replaced in the policy, got {res:?}");
+    assert!(lower.contains("\r\ncache-control: no-store\r\n"), "Expected the response not to be cached, got {res:?}");
+    assert!(res.ends_with(&format!("\r\n\r\n{page}")), "Expected the untouched file, got {res:?}");
+}
+
+#[tokio::test]
+async fn html_gets_preload_links() {
+    let server: TestServer = common::spawn(
+        "www-preload",
+        "preload:\n  '/':\n  - href: '/app.css'\n    as: 'style'\n  - href: '/font.woff2'\n    as: 'font'\n    crossorigin: true\n",
+    )
+    .await;
+    std::fs::write(server.site.join("app.css"), "body {}").unwrap();
+
+    let res: String = server.request("GET", "/index.html", "").await;
+    assert!(
+        res.to_ascii_lowercase()
+            .contains("\r\nlink: </app.css>; rel=preload; as=style, </font.woff2>; rel=preload; as=font; crossorigin\r\n"),
+        "Expected the preload links, got {res:?}"
+    );
+
+    // Other files don't preload anything
+    let res: String = server.request("GET", "/app.css", "").await;
+    assert!(!res.to_ascii_lowercase().contains("\r\nlink:"), "Expected no links, got {res:?}");
+}
+
+#[tokio::test]
+async fn unknown_extensions_are_sniffed() {
+    let server: TestServer = common::spawn("www-sniff-content", "sniff_content: true\n").await;
+    std::fs::write(server.site.join("logo"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+    std::fs::write(server.site.join("report.bin"), b"%PDF-1.7\n").unwrap();
+    std::fs::write(server.site.join("notes"), b"Just some notes").unwrap();
+    std::fs::write(server.site.join("fake.zip"), b"\x89PNG\r\n\x1a\n").unwrap();
+
+    for (path, mime) in [
+        ("/logo", "image/png"),
+        ("/report.bin", "application/pdf"),
+        ("/notes", "text/plain; charset=utf-8"),
+        ("/fake.zip", "application/zip"),
+    ] {
+        let res: String = server.request("GET", path, "").await;
+        assert!(res.to_ascii_lowercase().contains(&format!("\r\ncontent-type: {mime}\r\n")), "Expected '{mime}' for '{path}', got {res:?}");
+    }
+}